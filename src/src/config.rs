@@ -1,8 +1,21 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 const PRODUCT_NAME: &str = "Aitify";
+const CURRENT_CONFIG_VERSION: i32 = 2;
+const SUPPORTED_LANGUAGES: [&str; 2] = ["zh-CN", "en-US"];
+const FOCUS_TARGETS: [&str; 3] = ["auto", "main", "none"];
+const DURATION_STYLES: [&str; 3] = ["chinese", "hms", "clock"];
+const TRAY_CLICK_BEHAVIORS: [&str; 3] = ["restore", "toggle", "none"];
+const URGENCY_LEVELS: [&str; 3] = ["low", "normal", "critical"];
+const NOTIFY_MODES: [&str; 3] = ["completion", "first_reply", "both"];
+// 支持 first_reply 的来源
+const FIRST_REPLY_SOURCES: [&str; 3] = ["claude", "codex", "gemini"];
+const NOTIFICATION_TYPES: [&str; 4] = ["complete", "confirm", "error", "reply"];
+const ROUTING_CHANNELS: [&str; 2] = ["desktop", "hooks"];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
@@ -14,51 +27,284 @@ pub struct AppConfig {
     pub channels: ChannelsConfig,
     #[serde(default)]
     pub sources: SourcesConfig,
+    #[serde(default)]
+    pub confirm_alert: ConfirmAlertConfig,
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    #[serde(default)]
+    pub local_api: LocalApiConfig,
+    #[serde(default)]
+    pub turn_log: TurnLogConfig,
+    // 按 来源 -> 通知类型 -> 通道列表 路由，来源可用 "*" 匹配所有来源；
+    // 没有对应条目时回退到来源自身的通道开关
+    #[serde(default)]
+    pub routing: HashMap<String, HashMap<String, Vec<String>>>,
+    // 按模型名前缀覆盖内置价格表
+    #[serde(default)]
+    pub pricing: HashMap<String, ModelPrice>,
+    // 助手最后一条消息（去掉首尾空白）与其中某项完全相同或整体匹配该正则时，不发完成通知
+    #[serde(default)]
+    pub completion_ignore_patterns: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UiConfig {
     #[serde(default = "default_language")]
     pub language: String,
+    // 通知文案语言，为空时跟随 language
+    #[serde(default)]
+    pub notify_language: String,
+    // 通知中耗时的格式：chinese / hms / clock，为空时跟随通知语言
+    #[serde(default)]
+    pub duration_style: String,
     #[serde(default)]
     pub autostart: bool,
     #[serde(default)]
     pub silent_start: bool,
+    // 0 表示永不清理
+    #[serde(default = "default_watch_log_retention_days")]
+    pub watch_log_retention_days: i32,
+    // 监控轮询间隔（毫秒），启动时限制在 100–10000 之间
+    #[serde(default = "default_watch_interval_ms")]
+    pub watch_interval_ms: i32,
+    // 来源的会话文件仍在写入、却超过该分钟数没有读到新内容时报告停滞；0 表示不检查
+    #[serde(default = "default_watch_stale_minutes")]
+    pub watch_stale_minutes: u32,
+    #[serde(default)]
+    pub quiet_hours: QuietHoursConfig,
+    // 两次通知之间的最小间隔（毫秒），完成与确认分开计算；0 表示不限制
+    #[serde(default)]
+    pub notify_cooldown_ms: i64,
+    // 同一来源在该窗口内内容相同的完成通知只发一次；0 表示不去重
+    #[serde(default = "default_completion_dedupe_ms")]
+    pub completion_dedupe_ms: i64,
+    // 完成通知发出时把窗口拉到前台；focus_target 为 auto / main / none
+    #[serde(default)]
+    pub auto_focus_on_notify: bool,
+    #[serde(default)]
+    pub force_maximize_on_focus: bool,
+    #[serde(default = "default_focus_target")]
+    pub focus_target: String,
+    // 托盘左键与“打开”菜单的行为：restore 显示并聚焦 / toggle 显示与隐藏切换 / none 不处理
+    #[serde(default = "default_tray_click_behavior")]
+    pub tray_click_behavior: String,
+    // 按任务工作目录过滤：allowlist 非空时必须命中其一，命中 blocklist 的一律不通知。
+    // 含 * ? [ 的条目按 glob 匹配整个路径，否则按子串匹配，均不区分大小写
+    #[serde(default)]
+    pub cwd_allowlist: Vec<String>,
+    #[serde(default)]
+    pub cwd_blocklist: Vec<String>,
+    // 主窗口处于焦点时不发桌面通知，其他通道照常
+    #[serde(default)]
+    pub suppress_when_focused: bool,
+    // 大于 0 时，用户在该秒数内有键盘 / 鼠标输入就不发桌面通知；无法获取空闲时间时照常发送
+    #[serde(default)]
+    pub only_when_idle_seconds: i32,
+    // 同一来源在该窗口（毫秒）内的完成通知合并为一条汇总，0 表示不汇总
+    #[serde(default)]
+    pub digest_window_ms: i64,
+    // 桌面通知标题前加上 cwd 的最后一级目录名，如 "[myrepo] CLAUDE 任务完成"
+    #[serde(default)]
+    pub title_include_project: bool,
 }
 
 fn default_language() -> String { "zh-CN".to_string() }
+fn default_focus_target() -> String { "auto".to_string() }
+fn default_tray_click_behavior() -> String { "restore".to_string() }
+fn default_completion_dedupe_ms() -> i64 { 3000 }
+fn default_watch_log_retention_days() -> i32 { 7 }
+fn default_watch_interval_ms() -> i32 { 1000 }
+fn default_watch_stale_minutes() -> u32 { 30 }
 
 impl Default for UiConfig {
     fn default() -> Self {
         Self {
             language: default_language(),
+            notify_language: String::new(),
+            duration_style: String::new(),
             autostart: false,
             silent_start: false,
+            watch_log_retention_days: default_watch_log_retention_days(),
+            watch_interval_ms: default_watch_interval_ms(),
+            watch_stale_minutes: default_watch_stale_minutes(),
+            quiet_hours: QuietHoursConfig::default(),
+            notify_cooldown_ms: 0,
+            completion_dedupe_ms: default_completion_dedupe_ms(),
+            auto_focus_on_notify: false,
+            force_maximize_on_focus: false,
+            focus_target: default_focus_target(),
+            tray_click_behavior: default_tray_click_behavior(),
+            cwd_allowlist: Vec::new(),
+            cwd_blocklist: Vec::new(),
+            suppress_when_focused: false,
+            only_when_idle_seconds: 0,
+            digest_window_ms: 0,
+            title_include_project: false,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+// 免打扰时段：start/end 为本地时间 "HH:MM"，允许跨越午夜；channels 为需要静音的通道
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuietHoursConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_quiet_hours_start")]
+    pub start: String,
+    #[serde(default = "default_quiet_hours_end")]
+    pub end: String,
+    #[serde(default = "default_quiet_hours_channels")]
+    pub channels: Vec<String>,
+}
+
+fn default_quiet_hours_start() -> String { "23:00".to_string() }
+fn default_quiet_hours_end() -> String { "08:00".to_string() }
+fn default_quiet_hours_channels() -> Vec<String> { vec!["desktop".to_string()] }
+
+impl Default for QuietHoursConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: default_quiet_hours_start(),
+            end: default_quiet_hours_end(),
+            channels: default_quiet_hours_channels(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChannelsConfig {
     #[serde(default)]
     pub desktop: DesktopConfig,
+    // 各通道消息正文的最大字符数；桌面通知另有更小的上限
+    #[serde(default = "default_max_message_chars")]
+    pub max_message_chars: usize,
+    #[serde(default)]
+    pub urgency: UrgencyConfig,
+}
+
+fn default_max_message_chars() -> usize { 3500 }
+
+impl Default for ChannelsConfig {
+    fn default() -> Self {
+        Self {
+            desktop: DesktopConfig::default(),
+            max_message_chars: default_max_message_chars(),
+            urgency: UrgencyConfig::default(),
+        }
+    }
+}
+
+// 各类通知的紧急程度：low / normal / critical，由各通道映射为自身的优先级
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UrgencyConfig {
+    #[serde(default = "default_normal_urgency")]
+    pub complete: String,
+    #[serde(default = "default_critical_urgency")]
+    pub confirm: String,
+    #[serde(default = "default_normal_urgency")]
+    pub error: String,
+}
+
+fn default_normal_urgency() -> String { "normal".to_string() }
+fn default_critical_urgency() -> String { "critical".to_string() }
+
+impl Default for UrgencyConfig {
+    fn default() -> Self {
+        Self {
+            complete: default_normal_urgency(),
+            confirm: default_critical_urgency(),
+            error: default_normal_urgency(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DesktopConfig {
     #[serde(default = "default_true")]
     pub enabled: bool,
+    // 期望的通知停留时长（毫秒）；Windows 只支持短 / 长两档，>= 9000 时使用长档
+    #[serde(default = "default_balloon_ms")]
+    pub balloon_ms: u32,
 }
 
 fn default_true() -> bool { true }
 
+fn default_balloon_ms() -> u32 { 6000 }
+// 系统通知最长也只停留几十秒，超过一分钟的值视为误填
+const BALLOON_MS_MAX: u32 = 60_000;
+
 impl Default for DesktopConfig {
     fn default() -> Self {
-        Self { enabled: true }
+        Self { enabled: true, balloon_ms: default_balloon_ms() }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ConfirmAlertConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    // 非空时用正则替代内置关键词匹配，无效的表达式会被跳过。
+    #[serde(default)]
+    pub confirm_regexes: Vec<String>,
+    // 本轮距上一条用户消息不足该秒数时不发确认提醒，改走普通完成通知；0 表示不限制
+    #[serde(default)]
+    pub confirm_min_duration_seconds: i32,
+    // 命中的提示还须以问号结尾或带选项列表才算待确认，减少“已同意”之类的误报
+    #[serde(default)]
+    pub confirm_require_question: bool,
+}
+
+// 通知发出后执行的用户命令，为空时不执行
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct HooksConfig {
+    #[serde(default)]
+    pub on_complete_command: String,
+    #[serde(default)]
+    pub on_confirm_command: String,
+}
+
+// 把每个检测到的轮次追加到数据目录下的 turns.jsonl，供自行分析
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TurnLogConfig {
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+// 本机 HTTP 接口（仅监听 127.0.0.1），供外部脚本触发通知；修改后需重启应用生效
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalApiConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_local_api_port")]
+    pub port: u16,
+    // 启用时必填，请求需携带 Authorization: Bearer <token>
+    #[serde(default)]
+    pub token: String,
+}
+
+fn default_local_api_port() -> u16 { 17265 }
+
+impl Default for LocalApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_local_api_port(),
+            token: String::new(),
+        }
+    }
+}
+
+// 单位：美元 / 百万 token；cached_input 为空时按 input 计价
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModelPrice {
+    pub input: f64,
+    #[serde(default)]
+    pub cached_input: Option<f64>,
+    pub output: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SourcesConfig {
     #[serde(default)]
     pub claude: SourceConfig,
@@ -70,6 +316,10 @@ pub struct SourcesConfig {
     pub qwen: SourceConfig,
     #[serde(default)]
     pub opencode: SourceConfig,
+    #[serde(default = "default_aider_source")]
+    pub aider: SourceConfig,
+    #[serde(default)]
+    pub cursor: SourceConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -78,10 +328,50 @@ pub struct SourceConfig {
     pub enabled: bool,
     #[serde(default)]
     pub min_duration_minutes: i32,
+    // 覆盖 start_watch 传入的去抖窗口（毫秒），为空时使用调用参数
+    #[serde(default)]
+    pub quiet_ms: Option<i32>,
+    // 开始跟随文件时，把该窗口（毫秒）内刚结束的轮次当作实时处理并补发通知；0 表示不补发。
+    // 为空时 Claude 取 max(2 × quiet_ms, 15000)，Codex 取 CODEX_SEED_CATCHUP_MS（默认 30000），Gemini 不补发
+    #[serde(default)]
+    pub seed_catchup_ms: Option<i64>,
+    // 覆盖默认的日志目录；相对路径基于用户主目录，支持 ~/ 前缀
+    #[serde(default)]
+    pub log_dir: Option<String>,
+    // 按文件名匹配的 glob（如 session-*.jsonl），替代来源默认的文件筛选规则
+    #[serde(default)]
+    pub pattern: Option<String>,
+    // 完成通知中附带助手最后一条消息的摘录
+    #[serde(default = "default_true")]
+    pub include_summary: bool,
+    // 完成通知中附带本轮 token 用量与估算费用（目前 Claude / Codex 提供）
+    #[serde(default)]
+    pub show_token_usage: bool,
+    #[serde(default)]
+    pub show_cost: bool,
+    // 本轮没有调用工具（纯问答）时不发完成通知；Claude / Codex / Gemini 生效
+    #[serde(default)]
+    pub require_tool_use: bool,
+    // 仅 Claude：同时通知子代理（isSidechain）的完成
+    #[serde(default)]
+    pub notify_subagents: bool,
+    // 仅 Codex：只跟随首行是 Codex 会话头的 .jsonl，避免目录中混入的其他文件被当作会话
+    #[serde(default)]
+    pub require_session_header: bool,
+    // completion 轮次结束时通知 / first_reply 用户消息后的第一条回复立即通知 / both 两者都发
+    #[serde(default = "default_notify_mode")]
+    pub notify_mode: String,
+    // 桌面通知的标题 / 正文模板，支持 {source} {project} {duration} {summary} {type}；为空时使用内置文案
+    #[serde(default)]
+    pub title_template: String,
+    #[serde(default)]
+    pub body_template: String,
     #[serde(default)]
     pub channels: SourceChannelsConfig,
 }
 
+fn default_notify_mode() -> String { "completion".to_string() }
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SourceChannelsConfig {
     #[serde(default = "default_true")]
@@ -93,11 +383,58 @@ impl Default for SourceConfig {
         Self {
             enabled: true,
             min_duration_minutes: 0,
+            quiet_ms: None,
+            seed_catchup_ms: None,
+            log_dir: None,
+            pattern: None,
+            include_summary: true,
+            show_token_usage: false,
+            show_cost: false,
+            require_tool_use: false,
+            notify_subagents: false,
+            require_session_header: false,
+            notify_mode: default_notify_mode(),
+            title_template: String::new(),
+            body_template: String::new(),
             channels: SourceChannelsConfig::default(),
         }
     }
 }
 
+impl Default for SourcesConfig {
+    fn default() -> Self {
+        Self {
+            claude: SourceConfig::default(),
+            codex: SourceConfig::default(),
+            gemini: SourceConfig::default(),
+            qwen: SourceConfig::default(),
+            opencode: SourceConfig::default(),
+            aider: default_aider_source(),
+            cursor: SourceConfig::default(),
+        }
+    }
+}
+
+impl SourcesConfig {
+    pub fn get(&self, source: &str) -> Option<&SourceConfig> {
+        match source {
+            "claude" => Some(&self.claude),
+            "codex" => Some(&self.codex),
+            "gemini" => Some(&self.gemini),
+            "qwen" => Some(&self.qwen),
+            "opencode" => Some(&self.opencode),
+            "aider" => Some(&self.aider),
+            "cursor" => Some(&self.cursor),
+            _ => None,
+        }
+    }
+}
+
+// aider 需要扫描工作目录树，默认不启用
+fn default_aider_source() -> SourceConfig {
+    SourceConfig { enabled: false, ..SourceConfig::default() }
+}
+
 impl Default for SourceChannelsConfig {
     fn default() -> Self {
         Self { desktop: true }
@@ -107,10 +444,17 @@ impl Default for SourceChannelsConfig {
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
-            version: 2,
+            version: CURRENT_CONFIG_VERSION,
             ui: UiConfig::default(),
             channels: ChannelsConfig::default(),
             sources: SourcesConfig::default(),
+            confirm_alert: ConfirmAlertConfig::default(),
+            hooks: HooksConfig::default(),
+            local_api: LocalApiConfig::default(),
+            turn_log: TurnLogConfig::default(),
+            routing: HashMap::new(),
+            pricing: HashMap::new(),
+            completion_ignore_patterns: Vec::new(),
         }
     }
 }
@@ -152,6 +496,11 @@ pub fn get_settings_path() -> PathBuf {
     get_data_dir().join("settings.json")
 }
 
+// 上一次成功保存前的配置，主文件损坏时回退到它
+fn get_backup_settings_path() -> PathBuf {
+    get_data_dir().join("settings.json.bak")
+}
+
 pub fn load_config() -> Result<AppConfig, Box<dyn std::error::Error>> {
     let path = get_settings_path();
 
@@ -159,19 +508,271 @@ pub fn load_config() -> Result<AppConfig, Box<dyn std::error::Error>> {
         return Ok(AppConfig::default());
     }
 
-    let content = fs::read_to_string(&path)?;
-    let config: AppConfig = serde_json::from_str(&content)?;
+    let (config, migrated) = match read_config_file(&path) {
+        Ok(result) => result,
+        Err(e) => {
+            // 主文件损坏（如写入中途进程被结束）时读取备份，但不写回，以免覆盖用户手动编辑的内容
+            let (config, _) = read_config_file(&get_backup_settings_path()).map_err(|_| e)?;
+            eprintln!("settings.json is unreadable, using settings.json.bak");
+            return Ok(config);
+        }
+    };
+    if migrated {
+        if let Err(e) = save_config(&config) {
+            eprintln!("Failed to save migrated config: {}", e);
+        }
+    }
+    Ok(config)
+}
+
+// 返回配置以及是否经过了版本迁移
+fn read_config_file(path: &Path) -> Result<(AppConfig, bool), Box<dyn std::error::Error>> {
+    let content = fs::read_to_string(path)?;
+    let value: serde_json::Value = serde_json::from_str(&content)?;
+    let migrated = config_version(&value) < CURRENT_CONFIG_VERSION;
+    Ok((migrate_config(value)?, migrated))
+}
+
+// 手动编辑后重新读取；解析失败时给出行列号，便于定位
+pub fn reload_config() -> Result<AppConfig, String> {
+    let path = get_settings_path();
+    if !path.exists() {
+        return Ok(AppConfig::default());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let config = parse_config_text(&content)?;
+    validate_config(&config).map_err(|errors| errors.join("；"))?;
     Ok(config)
 }
 
+fn parse_config_text(content: &str) -> Result<AppConfig, String> {
+    let value: serde_json::Value = serde_json::from_str(content)
+        .map_err(|e| format!("settings.json 第 {} 行第 {} 列解析失败：{}", e.line(), e.column(), e))?;
+    migrate_config(value).map_err(|e| format!("settings.json 字段类型错误：{}", e))
+}
+
+fn config_version(value: &serde_json::Value) -> i32 {
+    value
+        .get("version")
+        .and_then(|v| v.as_i64())
+        .map(|v| v as i32)
+        .unwrap_or(1)
+}
+
+// 按 version 逐级升级旧版 settings.json，缺少 version 的文件视为 v1
+pub fn migrate_config(mut value: serde_json::Value) -> Result<AppConfig, serde_json::Error> {
+    if config_version(&value) < 2 {
+        migrate_v1_to_v2(&mut value);
+    }
+
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert("version".to_string(), CURRENT_CONFIG_VERSION.into());
+    }
+    serde_json::from_value(value)
+}
+
+// v1 把界面与通道选项平铺在顶层：language / autostart / silent_start / desktop_enabled
+fn migrate_v1_to_v2(value: &mut serde_json::Value) {
+    let Some(obj) = value.as_object_mut() else {
+        return;
+    };
+
+    let mut ui = match obj.remove("ui") {
+        Some(serde_json::Value::Object(ui)) => ui,
+        _ => serde_json::Map::new(),
+    };
+    for key in ["language", "autostart", "silent_start"] {
+        if let Some(v) = obj.remove(key) {
+            ui.entry(key.to_string()).or_insert(v);
+        }
+    }
+    obj.insert("ui".to_string(), serde_json::Value::Object(ui));
+
+    if let Some(enabled) = obj.remove("desktop_enabled") {
+        let channels = obj
+            .entry("channels")
+            .or_insert_with(|| serde_json::json!({}));
+        let desktop = channels
+            .as_object_mut()
+            .map(|channels| channels.entry("desktop").or_insert_with(|| serde_json::json!({})))
+            .and_then(|desktop| desktop.as_object_mut());
+        if let Some(desktop) = desktop {
+            desktop.entry("enabled").or_insert(enabled);
+        }
+    }
+}
+
+// 收集所有不合法的字段，供前端一次性展示
+pub fn validate_config(config: &AppConfig) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+
+    if !SUPPORTED_LANGUAGES.contains(&config.ui.language.as_str()) {
+        errors.push(format!("ui.language 不支持：{}", config.ui.language));
+    }
+    let notify_language = config.ui.notify_language.trim();
+    if !notify_language.is_empty() && !SUPPORTED_LANGUAGES.contains(&notify_language) {
+        errors.push(format!("ui.notify_language 不支持：{}", notify_language));
+    }
+    let duration_style = config.ui.duration_style.trim();
+    if !duration_style.is_empty() && !DURATION_STYLES.contains(&duration_style) {
+        errors.push(format!("ui.duration_style 应为 chinese / hms / clock：{}", duration_style));
+    }
+    if !FOCUS_TARGETS.contains(&config.ui.focus_target.as_str()) {
+        errors.push(format!("ui.focus_target 应为 auto / main / none：{}", config.ui.focus_target));
+    }
+    if !TRAY_CLICK_BEHAVIORS.contains(&config.ui.tray_click_behavior.as_str()) {
+        errors.push(format!("ui.tray_click_behavior 应为 restore / toggle / none：{}", config.ui.tray_click_behavior));
+    }
+    let urgency = &config.channels.urgency;
+    for (kind, level) in [("complete", &urgency.complete), ("confirm", &urgency.confirm), ("error", &urgency.error)] {
+        if !URGENCY_LEVELS.contains(&level.as_str()) {
+            errors.push(format!("channels.urgency.{} 应为 low / normal / critical：{}", kind, level));
+        }
+    }
+    if config.ui.watch_log_retention_days < 0 {
+        errors.push("ui.watch_log_retention_days 不能小于 0".to_string());
+    }
+    if config.ui.notify_cooldown_ms < 0 {
+        errors.push("ui.notify_cooldown_ms 不能小于 0".to_string());
+    }
+    if config.ui.completion_dedupe_ms < 0 {
+        errors.push("ui.completion_dedupe_ms 不能小于 0".to_string());
+    }
+    if config.ui.only_when_idle_seconds < 0 {
+        errors.push("ui.only_when_idle_seconds 不能小于 0".to_string());
+    }
+    if config.ui.digest_window_ms < 0 {
+        errors.push("ui.digest_window_ms 不能小于 0".to_string());
+    }
+    for (key, value) in [("start", &config.ui.quiet_hours.start), ("end", &config.ui.quiet_hours.end)] {
+        if chrono::NaiveTime::parse_from_str(value.trim(), "%H:%M").is_err() {
+            errors.push(format!("ui.quiet_hours.{} 应为 HH:MM 格式：{}", key, value));
+        }
+    }
+    if config.confirm_alert.confirm_min_duration_seconds < 0 {
+        errors.push("confirm_alert.confirm_min_duration_seconds 不能小于 0".to_string());
+    }
+    if config.channels.max_message_chars == 0 {
+        errors.push("channels.max_message_chars 不能为 0".to_string());
+    }
+    if !(1..=BALLOON_MS_MAX).contains(&config.channels.desktop.balloon_ms) {
+        errors.push(format!("channels.desktop.balloon_ms 应在 1–{} 之间：{}", BALLOON_MS_MAX, config.channels.desktop.balloon_ms));
+    }
+    if config.local_api.enabled && config.local_api.port == 0 {
+        errors.push("local_api.port 不能为 0".to_string());
+    }
+    if config.local_api.enabled && config.local_api.token.trim().is_empty() {
+        errors.push("启用 local_api 时 local_api.token 不能为空".to_string());
+    }
+
+    let sources = &config.sources;
+    for (name, source) in [
+        ("claude", &sources.claude),
+        ("codex", &sources.codex),
+        ("gemini", &sources.gemini),
+        ("qwen", &sources.qwen),
+        ("opencode", &sources.opencode),
+        ("aider", &sources.aider),
+        ("cursor", &sources.cursor),
+    ] {
+        if source.min_duration_minutes < 0 {
+            errors.push(format!("sources.{}.min_duration_minutes 不能小于 0", name));
+        }
+        if source.quiet_ms.is_some_and(|ms| ms < 0) {
+            errors.push(format!("sources.{}.quiet_ms 不能小于 0", name));
+        }
+        if source.seed_catchup_ms.is_some_and(|ms| ms < 0) {
+            errors.push(format!("sources.{}.seed_catchup_ms 不能小于 0", name));
+        }
+        if !NOTIFY_MODES.contains(&source.notify_mode.as_str()) {
+            errors.push(format!("sources.{}.notify_mode 应为 completion / first_reply / both：{}", name, source.notify_mode));
+        } else if source.notify_mode != "completion" && !FIRST_REPLY_SOURCES.contains(&name) {
+            errors.push(format!("sources.{}.notify_mode 仅 Claude / Codex / Gemini 支持 first_reply / both", name));
+        }
+    }
+
+    for (source, types) in &config.routing {
+        if source != "*" && sources.get(source).is_none() {
+            errors.push(format!("routing 中的来源不支持：{}", source));
+        }
+        for (notification_type, channels) in types {
+            if !NOTIFICATION_TYPES.contains(&notification_type.as_str()) {
+                errors.push(format!("routing.{} 中的通知类型应为 complete / confirm / error / reply：{}", source, notification_type));
+            }
+            for channel in channels {
+                if !ROUTING_CHANNELS.contains(&channel.as_str()) {
+                    errors.push(format!("routing.{}.{} 中的通道不支持：{}", source, notification_type, channel));
+                }
+            }
+        }
+    }
+
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
 pub fn save_config(config: &AppConfig) -> Result<(), Box<dyn std::error::Error>> {
     let dir = get_data_dir();
     fs::create_dir_all(&dir)?;
 
-    let path = get_settings_path();
     let content = serde_json::to_string_pretty(config)?;
-    fs::write(&path, content)?;
+    write_config_atomically(&get_settings_path(), &get_backup_settings_path(), &content)?;
+
+    Ok(())
+}
 
+// 先写同目录下的临时文件再 rename 覆盖，避免进程中途退出留下截断的 settings.json；
+// 覆盖前把仍能解析的旧文件复制为备份
+fn write_config_atomically(path: &Path, backup: &Path, content: &str) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let tmp = path.with_extension("json.tmp");
+    {
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+    }
+    if read_config_file(path).is_ok() {
+        fs::copy(path, backup)?;
+    }
+    fs::rename(&tmp, path)
+}
+
+// 串行化 patch_config 的读改写，避免并发的单字段修改互相覆盖
+static CONFIG_PATCH_LOCK: Mutex<()> = Mutex::new(());
+
+// 按点分路径（如 ui.autostart）修改单个字段并保存，返回修改后的完整配置
+pub fn patch_config(path: &str, value: serde_json::Value) -> Result<AppConfig, String> {
+    let _guard = CONFIG_PATCH_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let config = load_config().map_err(|e| e.to_string())?;
+    let mut root = serde_json::to_value(&config).map_err(|e| e.to_string())?;
+    apply_config_patch(&mut root, path, value)?;
+    let patched: AppConfig = serde_json::from_value(root).map_err(|e| format!("{} 的值类型错误：{}", path, e))?;
+    validate_config(&patched).map_err(|errors| errors.join("；"))?;
+    save_config(&patched).map_err(|e| e.to_string())?;
+    Ok(patched)
+}
+
+// 路径必须已存在；原值不为 null 时新值的 JSON 类型须与之一致
+fn apply_config_patch(root: &mut serde_json::Value, path: &str, value: serde_json::Value) -> Result<(), String> {
+    use serde_json::Value;
+
+    let target = path
+        .split('.')
+        .try_fold(root, |node, key| node.as_object_mut().and_then(|map| map.get_mut(key)))
+        .ok_or_else(|| format!("配置项不存在：{}", path))?;
+    let same_type = matches!(
+        (&*target, &value),
+        (Value::Null, _)
+            | (Value::Bool(_), Value::Bool(_))
+            | (Value::Number(_), Value::Number(_))
+            | (Value::String(_), Value::String(_))
+            | (Value::Array(_), Value::Array(_))
+            | (Value::Object(_), Value::Object(_))
+    );
+    if !same_type {
+        return Err(format!("{} 的值类型不匹配", path));
+    }
+    *target = value;
     Ok(())
 }
 
@@ -183,6 +784,15 @@ pub fn get_config_path() -> PathBuf {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_config_text_reports_line_and_column() {
+        let config = parse_config_text("{ \"ui\": { \"language\": \"en-US\" } }").expect("valid config should parse");
+        assert_eq!(config.ui.language, "en-US");
+
+        let error = parse_config_text("{\n  \"ui\": {\n    \"language\": \"en-US\",\n  }\n}").unwrap_err();
+        assert!(error.starts_with("settings.json 第 4 行第 3 列"), "{}", error);
+    }
+
     #[test]
     fn test_app_config_defaults_enable_all_sources_and_desktop_channels() {
         let config = AppConfig::default();
@@ -192,11 +802,100 @@ mod tests {
         assert!(config.sources.gemini.enabled);
         assert!(config.sources.qwen.enabled);
         assert!(config.sources.opencode.enabled);
+        assert!(config.sources.cursor.enabled);
 
         assert!(config.sources.claude.channels.desktop);
         assert!(config.sources.codex.channels.desktop);
         assert!(config.sources.gemini.channels.desktop);
         assert!(config.sources.qwen.channels.desktop);
         assert!(config.sources.opencode.channels.desktop);
+        assert!(config.sources.cursor.channels.desktop);
+    }
+
+    #[test]
+    fn test_write_config_atomically_keeps_last_good_backup() {
+        let dir = std::env::temp_dir().join(format!("aitify-config-write-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("settings.json");
+        let backup = dir.join("settings.json.bak");
+        let content = |language: &str| {
+            let mut config = AppConfig::default();
+            config.ui.language = language.to_string();
+            serde_json::to_string_pretty(&config).unwrap()
+        };
+
+        write_config_atomically(&path, &backup, &content("zh-CN")).unwrap();
+        assert!(!backup.exists());
+        write_config_atomically(&path, &backup, &content("en-US")).unwrap();
+        assert_eq!(read_config_file(&path).unwrap().0.ui.language, "en-US");
+        assert_eq!(read_config_file(&backup).unwrap().0.ui.language, "zh-CN");
+
+        // 损坏的主文件不会覆盖已有备份
+        fs::write(&path, "{ \"ui\": ").unwrap();
+        write_config_atomically(&path, &backup, &content("en-US")).unwrap();
+        assert_eq!(read_config_file(&backup).unwrap().0.ui.language, "zh-CN");
+        assert!(!path.with_extension("json.tmp").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_apply_config_patch_checks_path_and_type() {
+        let mut root = serde_json::to_value(AppConfig::default()).unwrap();
+        apply_config_patch(&mut root, "ui.autostart", serde_json::json!(true)).unwrap();
+        apply_config_patch(&mut root, "sources.codex.quiet_ms", serde_json::json!(5000)).unwrap();
+        let config: AppConfig = serde_json::from_value(root.clone()).unwrap();
+        assert!(config.ui.autostart);
+        assert_eq!(config.sources.codex.quiet_ms, Some(5000));
+
+        assert!(apply_config_patch(&mut root, "ui.no_such_field", serde_json::json!(1)).is_err());
+        assert!(apply_config_patch(&mut root, "ui.autostart.nested", serde_json::json!(1)).is_err());
+        assert!(apply_config_patch(&mut root, "ui.autostart", serde_json::json!("yes")).is_err());
+    }
+
+    #[test]
+    fn test_validate_config_collects_errors() {
+        assert!(validate_config(&AppConfig::default()).is_ok());
+
+        let mut config = AppConfig::default();
+        config.ui.language = "fr".to_string();
+        config.ui.focus_target = "window".to_string();
+        config.ui.quiet_hours.end = "25:00".to_string();
+        config.sources.codex.quiet_ms = Some(-1);
+        config.sources.gemini.notify_mode = "both".to_string();
+        config.sources.qwen.notify_mode = "first_reply".to_string();
+        config.channels.desktop.balloon_ms = 0;
+        config.local_api.enabled = true;
+
+        let errors = validate_config(&config).unwrap_err();
+        assert_eq!(errors.len(), 7);
+        assert!(errors.iter().any(|e| e.contains("local_api.token")));
+        assert!(errors.iter().any(|e| e.contains("channels.desktop.balloon_ms")));
+        assert!(errors.iter().any(|e| e.contains("sources.codex.quiet_ms")));
+        assert!(errors.iter().any(|e| e.contains("sources.qwen.notify_mode")));
+
+        let mut config = AppConfig::default();
+        config.channels.desktop.balloon_ms = 60_001;
+        assert!(validate_config(&config).is_err());
+        config.channels.desktop.balloon_ms = 60_000;
+        assert!(validate_config(&config).is_ok());
+    }
+
+    #[test]
+    fn test_migrate_config_moves_v1_flat_fields() {
+        let v1 = serde_json::json!({
+            "language": "en-US",
+            "autostart": true,
+            "desktop_enabled": false,
+            "sources": { "codex": { "enabled": false } }
+        });
+
+        let config = migrate_config(v1).unwrap();
+
+        assert_eq!(config.version, CURRENT_CONFIG_VERSION);
+        assert_eq!(config.ui.language, "en-US");
+        assert!(config.ui.autostart);
+        assert!(!config.channels.desktop.enabled);
+        assert!(!config.sources.codex.enabled);
     }
 }
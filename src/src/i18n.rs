@@ -0,0 +1,125 @@
+use crate::config::UiConfig;
+
+// 通知文案；标题形如 "{来源} {complete_suffix}"
+pub struct NotifyStrings {
+    pub complete_suffix: &'static str,
+    pub reply_suffix: &'static str,
+    pub error_suffix: &'static str,
+    pub separator: &'static str,
+    pub complete_title: &'static str,
+    pub confirm_title: &'static str,
+    pub error_title: &'static str,
+    pub complete_body: &'static str,
+    pub confirm_body: &'static str,
+    pub error_body: &'static str,
+    pub duration_label: &'static str,
+    pub subagent_label: &'static str,
+    // ui.duration_style 为空时使用的耗时格式
+    duration_style: &'static str,
+    tasks_completed: fn(u32) -> String,
+}
+
+impl NotifyStrings {
+    pub fn format_tasks_completed(&self, count: u32) -> String {
+        (self.tasks_completed)(count)
+    }
+}
+
+static ZH_CN: NotifyStrings = NotifyStrings {
+    complete_suffix: "任务已完成",
+    reply_suffix: "已回复",
+    error_suffix: "任务出错",
+    separator: "：",
+    complete_title: "任务完成",
+    confirm_title: "待确认",
+    error_title: "出错",
+    complete_body: "任务已完成",
+    confirm_body: "需要你的确认",
+    error_body: "任务出错",
+    duration_label: "耗时",
+    subagent_label: "子代理",
+    duration_style: "chinese",
+    tasks_completed: |n| format!("{} 个任务已完成", n),
+};
+
+static EN_US: NotifyStrings = NotifyStrings {
+    complete_suffix: "task completed",
+    reply_suffix: "replied",
+    error_suffix: "task failed",
+    separator: ": ",
+    complete_title: "task done",
+    confirm_title: "needs confirmation",
+    error_title: "error",
+    complete_body: "Task completed",
+    confirm_body: "Waiting for your confirmation",
+    error_body: "Task failed",
+    duration_label: "took",
+    subagent_label: "subagent",
+    duration_style: "hms",
+    tasks_completed: |n| format!("{} tasks completed", n),
+};
+
+// notify_language 为空时跟随界面语言，未知语言回退到中文
+pub fn notify_strings(ui: &UiConfig) -> &'static NotifyStrings {
+    let language = Some(ui.notify_language.trim())
+        .filter(|lang| !lang.is_empty())
+        .unwrap_or(&ui.language);
+    if language.to_lowercase().starts_with("en") {
+        &EN_US
+    } else {
+        &ZH_CN
+    }
+}
+
+// ui.duration_style 为空时跟随通知语言：中文为 chinese，英文为 hms
+pub fn duration_style(ui: &UiConfig) -> &str {
+    match ui.duration_style.trim() {
+        "" => notify_strings(ui).duration_style,
+        style => style,
+    }
+}
+
+// chinese：“2 小时3分” / “1 分5秒” / “5 秒”；hms：“2h 3m” / “1m 5s” / “5s”，
+// 这两种超过一小时只显示到分钟；clock：“2:03:04”。未知格式按 chinese 处理
+pub fn format_duration(ms: i64, style: &str) -> String {
+    let total_seconds = ms.max(0) / 1000;
+    let (hours, minutes, seconds) = (total_seconds / 3600, total_seconds / 60 % 60, total_seconds % 60);
+    match style {
+        "clock" => format!("{}:{:02}:{:02}", hours, minutes, seconds),
+        "hms" if hours > 0 => format!("{}h {}m", hours, minutes),
+        "hms" if minutes > 0 => format!("{}m {}s", minutes, seconds),
+        "hms" => format!("{}s", seconds),
+        _ if hours > 0 => format!("{} 小时{}分", hours, minutes),
+        _ if minutes > 0 => format!("{} 分{}秒", minutes, seconds),
+        _ => format!("{} 秒", seconds),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_duration_boundaries() {
+        for (style, under_second, one_minute, over_hour) in [
+            ("chinese", "0 秒", "1 分0秒", "2 小时3分"),
+            ("hms", "0s", "1m 0s", "2h 3m"),
+            ("clock", "0:00:00", "0:01:00", "2:03:04"),
+        ] {
+            assert_eq!(format_duration(999, style), under_second);
+            assert_eq!(format_duration(60_000, style), one_minute);
+            assert_eq!(format_duration(7_384_000, style), over_hour);
+        }
+        assert_eq!(format_duration(-5, "hms"), "0s");
+    }
+
+    #[test]
+    fn test_duration_style_follows_notify_language() {
+        let mut ui = UiConfig::default();
+        assert_eq!(duration_style(&ui), "chinese");
+        ui.notify_language = "en-US".to_string();
+        assert_eq!(duration_style(&ui), "hms");
+        ui.duration_style = "clock".to_string();
+        assert_eq!(duration_style(&ui), "clock");
+    }
+}
@@ -0,0 +1,337 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::LocalApiConfig;
+use crate::notify::send_notifications;
+
+const MAX_BODY_BYTES: usize = 64 * 1024;
+const READ_TIMEOUT_MS: u64 = 5000;
+// 请求行与每个请求头的最大字节数、请求头数量上限
+const MAX_HEADER_LINE_BYTES: usize = 8 * 1024;
+const MAX_HEADERS: usize = 64;
+// 同时处理的连接数上限，超出时直接返回 503
+const MAX_CONNECTIONS: usize = 8;
+const JSON_CONTENT_TYPE: &str = "application/json; charset=utf-8";
+const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+#[derive(Debug, Deserialize)]
+struct NotifyRequest {
+    source: String,
+    task_info: String,
+    #[serde(default)]
+    duration_ms: Option<i64>,
+    #[serde(default)]
+    cwd: Option<String>,
+}
+
+#[derive(Debug)]
+struct HttpRequest {
+    method: String,
+    path: String,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+// 在后台线程中监听 127.0.0.1:<port>，未启用或未配置 token 时直接返回
+pub fn start_local_api(config: &LocalApiConfig) {
+    if !config.enabled {
+        return;
+    }
+    let token = config.token.trim().to_string();
+    if token.is_empty() {
+        eprintln!("Local api is enabled but local_api.token is empty, not starting");
+        return;
+    }
+
+    let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, config.port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Failed to start local api on port {}: {}", config.port, e);
+            return;
+        }
+    };
+
+    let active = Arc::new(AtomicUsize::new(0));
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            if active.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                active.fetch_sub(1, Ordering::SeqCst);
+                let _ = write_response(&mut stream, 503, JSON_CONTENT_TYPE, r#"{"error":"too many connections"}"#);
+                continue;
+            }
+            let token = token.clone();
+            let active = active.clone();
+            std::thread::spawn(move || {
+                handle_connection(stream, &token);
+                active.fetch_sub(1, Ordering::SeqCst);
+            });
+        }
+    });
+}
+
+fn handle_connection(mut stream: TcpStream, token: &str) {
+    let _ = stream.set_read_timeout(Some(Duration::from_millis(READ_TIMEOUT_MS)));
+
+    let (status, content_type, body) = match read_http_request(&mut BufReader::new(&stream)) {
+        Ok(request) if request.path == "/metrics" => metrics_response(&request, token),
+        Ok(request) => {
+            let (status, body) = route_request(&request, token);
+            (status, JSON_CONTENT_TYPE, body.to_string())
+        }
+        Err(e) => (400, JSON_CONTENT_TYPE, serde_json::json!({ "error": e }).to_string()),
+    };
+    let _ = write_response(&mut stream, status, content_type, &body);
+}
+
+// GET /metrics 返回 Prometheus 文本格式，与 /notify 使用同一个 token
+fn metrics_response(request: &HttpRequest, token: &str) -> (u16, &'static str, String) {
+    let error = |status: u16, message: &str| (status, JSON_CONTENT_TYPE, serde_json::json!({ "error": message }).to_string());
+    if request.method != "GET" {
+        return error(405, "method not allowed");
+    }
+    if is_cross_site(&request.headers) {
+        return error(403, "forbidden");
+    }
+    if !is_authorized(&request.headers, token) {
+        return error(401, "unauthorized");
+    }
+    (200, METRICS_CONTENT_TYPE, crate::metrics::render())
+}
+
+fn route_request(request: &HttpRequest, token: &str) -> (u16, serde_json::Value) {
+    if request.path != "/notify" {
+        return (404, serde_json::json!({ "error": "not found" }));
+    }
+    if request.method != "POST" {
+        return (405, serde_json::json!({ "error": "method not allowed" }));
+    }
+    if is_cross_site(&request.headers) {
+        return (403, serde_json::json!({ "error": "forbidden" }));
+    }
+    if !is_authorized(&request.headers, token) {
+        return (401, serde_json::json!({ "error": "unauthorized" }));
+    }
+    if !is_json_content_type(&request.headers) {
+        return (415, serde_json::json!({ "error": "content-type must be application/json" }));
+    }
+
+    let payload: NotifyRequest = match serde_json::from_slice(&request.body) {
+        Ok(payload) => payload,
+        Err(e) => return (400, serde_json::json!({ "error": e.to_string() })),
+    };
+
+    let result = tauri::async_runtime::block_on(send_notifications(
+        payload.source.trim(),
+        &payload.task_info,
+        payload.duration_ms,
+        payload.cwd.unwrap_or_default(),
+        false,
+        Some("complete"),
+    ));
+    match result {
+        Ok(value) => (200, value),
+        Err(e) => (500, serde_json::json!({ "error": e })),
+    }
+}
+
+// 浏览器发起的请求都会带 Origin，本机脚本不会；拒绝它们，避免网页跨站调用本接口
+fn is_cross_site(headers: &HashMap<String, String>) -> bool {
+    headers.contains_key("origin")
+}
+
+// 只接受 application/json：网页无需预检即可发送的 text/plain 等表单类型一律拒绝
+fn is_json_content_type(headers: &HashMap<String, String>) -> bool {
+    headers
+        .get("content-type")
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|mime| mime.trim().eq_ignore_ascii_case("application/json"))
+}
+
+// 接受 "Bearer <token>" 或直接传 token；token 为空时一律拒绝
+fn is_authorized(headers: &HashMap<String, String>, token: &str) -> bool {
+    if token.is_empty() {
+        return false;
+    }
+
+    headers
+        .get("authorization")
+        .map(|value| value.trim())
+        .map(|value| value.strip_prefix("Bearer ").unwrap_or(value).trim())
+        .is_some_and(|value| constant_time_eq(value.as_bytes(), token.as_bytes()))
+}
+
+// 比较耗时只与长度有关，不随第一个不同字节的位置变化
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+// 读取一行，超过 MAX_HEADER_LINE_BYTES 时报错，避免无换行的超长输入占满内存
+fn read_limited_line<R: BufRead>(reader: &mut R) -> Result<(usize, String), String> {
+    let mut line = String::new();
+    let bytes_read = reader
+        .by_ref()
+        .take(MAX_HEADER_LINE_BYTES as u64 + 1)
+        .read_line(&mut line)
+        .map_err(|e| e.to_string())?;
+    if bytes_read > MAX_HEADER_LINE_BYTES {
+        return Err("header line too long".to_string());
+    }
+    Ok((bytes_read, line))
+}
+
+fn read_http_request<R: BufRead>(reader: &mut R) -> Result<HttpRequest, String> {
+    let (_, request_line) = read_limited_line(reader)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err("invalid request line".to_string());
+    };
+    let path = target.split('?').next().unwrap_or(target).to_string();
+
+    let mut headers = HashMap::new();
+    for count in 0.. {
+        let (bytes_read, line) = read_limited_line(reader)?;
+        let line = line.trim_end_matches(['\r', '\n']);
+        if bytes_read == 0 || line.is_empty() {
+            break;
+        }
+        if count >= MAX_HEADERS {
+            return Err("too many headers".to_string());
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let content_length = headers
+        .get("content-length")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    if content_length > MAX_BODY_BYTES {
+        return Err("request body too large".to_string());
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+
+    Ok(HttpRequest {
+        method: method.to_ascii_uppercase(),
+        path,
+        headers,
+        body,
+    })
+}
+
+fn write_response(stream: &mut TcpStream, status: u16, content_type: &str, body: &str) -> std::io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        415 => "Unsupported Media Type",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_http_request_parses_headers_and_body() {
+        let raw = "POST /notify?x=1 HTTP/1.1\r\nHost: 127.0.0.1\r\nAuthorization: Bearer abc\r\nContent-Length: 18\r\n\r\n{\"source\":\"ci\"}xyz";
+        let request = read_http_request(&mut raw.as_bytes()).unwrap();
+
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/notify");
+        assert_eq!(request.body, b"{\"source\":\"ci\"}xyz");
+        assert!(is_authorized(&request.headers, "abc"));
+        assert!(!is_authorized(&request.headers, "other"));
+        assert!(!is_authorized(&request.headers, "ab"));
+        assert!(!is_authorized(&HashMap::new(), ""));
+    }
+
+    #[test]
+    fn test_read_http_request_limits_header_size_and_count() {
+        let long = format!("GET /metrics HTTP/1.1\r\nX-Long: {}\r\n\r\n", "a".repeat(MAX_HEADER_LINE_BYTES));
+        assert_eq!(read_http_request(&mut long.as_bytes()).unwrap_err(), "header line too long");
+
+        let many = format!("GET /metrics HTTP/1.1\r\n{}\r\n", "X-A: 1\r\n".repeat(MAX_HEADERS + 1));
+        assert_eq!(read_http_request(&mut many.as_bytes()).unwrap_err(), "too many headers");
+        let enough = format!("GET /metrics HTTP/1.1\r\n{}\r\n", "X-A: 1\r\n".repeat(MAX_HEADERS));
+        assert!(read_http_request(&mut enough.as_bytes()).is_ok());
+    }
+
+    #[test]
+    fn test_route_request_rejects_browser_and_non_json_requests() {
+        let mut request = HttpRequest {
+            method: "POST".to_string(),
+            path: "/notify".to_string(),
+            headers: HashMap::from([
+                ("authorization".to_string(), "Bearer secret".to_string()),
+                ("content-type".to_string(), "text/plain".to_string()),
+            ]),
+            body: b"not json".to_vec(),
+        };
+        assert_eq!(route_request(&request, "secret").0, 415);
+
+        request.headers.insert("content-type".to_string(), "application/json; charset=utf-8".to_string());
+        assert_eq!(route_request(&request, "secret").0, 400);
+
+        request.headers.insert("origin".to_string(), "https://example.com".to_string());
+        assert_eq!(route_request(&request, "secret").0, 403);
+    }
+
+    #[test]
+    fn test_route_request_rejects_bad_token_before_parsing_body() {
+        let request = HttpRequest {
+            method: "POST".to_string(),
+            path: "/notify".to_string(),
+            headers: HashMap::new(),
+            body: b"not json".to_vec(),
+        };
+
+        assert_eq!(route_request(&request, "secret").0, 401);
+        assert_eq!(route_request(&request, "").0, 401);
+    }
+
+    #[test]
+    fn test_metrics_response_requires_get_and_token() {
+        let mut request = HttpRequest {
+            method: "GET".to_string(),
+            path: "/metrics".to_string(),
+            headers: HashMap::new(),
+            body: Vec::new(),
+        };
+
+        assert_eq!(metrics_response(&request, "secret").0, 401);
+        request.headers.insert("authorization".to_string(), "secret".to_string());
+        let (status, content_type, body) = metrics_response(&request, "secret");
+        assert_eq!((status, content_type), (200, METRICS_CONTENT_TYPE));
+        assert!(body.contains("# TYPE aitify_watch_running gauge"));
+
+        request.headers.insert("origin".to_string(), "http://localhost:3000".to_string());
+        assert_eq!(metrics_response(&request, "secret").0, 403);
+
+        request.method = "POST".to_string();
+        assert_eq!(metrics_response(&request, "secret").0, 405);
+    }
+}
@@ -1,21 +1,35 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use serde::{Deserialize, Serialize};
-use tauri::{menu::{Menu, MenuItem}, tray::{MouseButton, TrayIconBuilder, TrayIconEvent}, Emitter, Manager, State};
+use tauri::{menu::{Menu, MenuItem}, tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent}, Emitter, Manager, State};
 
 mod config;
+mod i18n;
+mod local_api;
+mod metrics;
 mod notify;
+mod platform_focus;
+mod platform_idle;
+mod pricing;
+mod stats;
+mod turn_log;
 mod watch;
+mod watch_log;
 
-use config::{load_config, save_config as save_config_to_file, get_config_path, get_data_dir, AppConfig};
+use config::{load_config, migrate_config, reload_config as reload_config_from_file, save_config as save_config_to_file, validate_config, get_config_path, get_data_dir, AppConfig};
 use notify::send_notifications;
 use watch::start_watch as start_watch_fn;
 
 const AUTOSTART_REG_PATH: &str = r"Software\Microsoft\Windows\CurrentVersion\Run";
 const AUTOSTART_VALUE_NAME: &str = "Aitify";
 const AUTOSTART_SILENT_ARG: &str = "--autostart-silent";
+const REDACTED_TOKEN: &str = "<redacted>";
+// 诊断快照中附带的监听日志行数与每个会话文件的末尾行数
+const DIAGNOSTICS_LOG_LINES: usize = 200;
+const DIAGNOSTICS_SESSION_LINES: usize = 50;
 
 #[derive(Serialize)]
 struct MetaInfo {
@@ -25,9 +39,54 @@ struct MetaInfo {
     version: String,
 }
 
+#[derive(Serialize)]
+struct SessionTail {
+    source: String,
+    path: String,
+    lines: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Diagnostics {
+    captured_at: String,
+    os: String,
+    meta: MetaInfo,
+    config: AppConfig,
+    watch: watch::WatchDebugInfo,
+    watch_log: Vec<String>,
+    sessions: Vec<SessionTail>,
+}
+
 #[derive(Serialize)]
 struct WatchStatus {
     running: bool,
+    paused: bool,
+}
+
+// 清空监听日志的结果；skipped 为删除失败（如正在写入）而保留的文件数
+#[derive(Serialize)]
+struct ClearedWatchLogs {
+    removed: usize,
+    skipped: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StartupMode {
+    Shown,
+    Minimized,
+}
+
+// 配置了静默启动，或由带静默参数的开机自启拉起时，只保留托盘图标
+fn get_startup_mode<I, S>(silent_start: bool, args: I) -> StartupMode
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    if silent_start || args.into_iter().any(|arg| arg.as_ref() == AUTOSTART_SILENT_ARG) {
+        StartupMode::Minimized
+    } else {
+        StartupMode::Shown
+    }
 }
 
 #[derive(Deserialize)]
@@ -40,12 +99,15 @@ struct WatchStartPayload {
     gemini_quiet_ms: i32,
     #[serde(default = "default_claude_quiet_ms")]
     claude_quiet_ms: i32,
+    #[serde(default = "default_codex_quiet_ms")]
+    codex_quiet_ms: i32,
 }
 
 fn default_sources() -> String { "all".to_string() }
 fn default_interval_ms() -> i32 { 1000 }
 fn default_gemini_quiet_ms() -> i32 { 3000 }
 fn default_claude_quiet_ms() -> i32 { 3000 }
+fn default_codex_quiet_ms() -> i32 { 0 }
 
 #[derive(Deserialize)]
 struct TestNotifyPayload {
@@ -60,13 +122,30 @@ struct TestNotifyPayload {
 fn default_test_source() -> String { "claude".to_string() }
 
 struct AppState {
-    watch_stop: Arc<Mutex<Option<Box<dyn FnOnce() + Send>>>>,
+    watch_stop: Arc<Mutex<Option<watch::StopHandle>>>,
+    watch_paused: Arc<AtomicBool>,
+    watch_debug: watch::WatchDebugHandle,
+    notification_history: notify::NotificationHistory,
+    notify_cooldown: notify::NotifyCooldown,
+    completion_dedupe: notify::CompletionDedupe,
+    snooze: notify::Snooze,
+    completion_digest: notify::CompletionDigest,
+    // 主窗口当前是否处于焦点，由窗口事件更新
+    window_focused: AtomicBool,
 }
 
 impl Default for AppState {
     fn default() -> Self {
         Self {
             watch_stop: Arc::new(Mutex::new(None)),
+            watch_paused: Arc::new(AtomicBool::new(false)),
+            watch_debug: Arc::new(Mutex::new(Vec::new())),
+            notification_history: notify::NotificationHistory::default(),
+            notify_cooldown: notify::NotifyCooldown::default(),
+            completion_dedupe: notify::CompletionDedupe::default(),
+            snooze: notify::Snooze::load(),
+            completion_digest: notify::CompletionDigest::default(),
+            window_focused: AtomicBool::new(false),
         }
     }
 }
@@ -76,22 +155,39 @@ fn start_watch_default(app: &tauri::AppHandle, state: &AppState) -> Result<(), S
     if guard.is_some() {
         return Ok(());
     }
+    // 新启动的监听总是从未暂停开始
+    state.watch_paused.store(false, Ordering::Relaxed);
 
     let app_handle = app.clone();
+    let interval_ms = load_config()
+        .map(|config| config.ui.watch_interval_ms)
+        .unwrap_or_else(|_| default_interval_ms());
     let stop = start_watch_fn(
         "all",
-        default_interval_ms(),
+        interval_ms,
         default_gemini_quiet_ms(),
         default_claude_quiet_ms(),
-        move |line: String| {
-            let _ = app_handle.emit("watch-log", line);
+        default_codex_quiet_ms(),
+        watch::WatchControl {
+            paused: state.watch_paused.clone(),
+            debug: state.watch_debug.clone(),
         },
+        watch_log_emitter(app_handle),
     ).map_err(|e| e.to_string())?;
 
     *guard = Some(stop);
     Ok(())
 }
 
+// 监听日志只以 watch-log-batch 事件（载荷为若干行组成的数组）发给前端，不再逐行发送 watch-log；
+// 前端需要实时日志时监听 watch-log-batch
+fn watch_log_emitter(app: tauri::AppHandle) -> impl FnMut(String) + Send + 'static {
+    let batcher = watch_log::LogBatcher::new(move |lines: Vec<String>| {
+        let _ = app.emit("watch-log-batch", lines);
+    });
+    move |line: String| batcher.push(line)
+}
+
 #[cfg(target_os = "windows")]
 fn apply_windows_autostart(enabled: bool, silent_start: bool) -> Result<(), String> {
     use windows_registry::*;
@@ -121,6 +217,17 @@ fn apply_windows_autostart(_enabled: bool, _silent_start: bool) -> Result<(), St
     Ok(())
 }
 
+fn open_with_system(path: &std::path::Path) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    let mut command = std::process::Command::new("explorer");
+    #[cfg(target_os = "macos")]
+    let mut command = std::process::Command::new("open");
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut command = std::process::Command::new("xdg-open");
+
+    command.arg(path).spawn().map(|_| ()).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 fn get_meta(app_handle: tauri::AppHandle) -> MetaInfo {
     MetaInfo {
@@ -138,47 +245,327 @@ fn get_config() -> Result<AppConfig, String> {
 
 #[tauri::command]
 fn save_config(config: AppConfig) -> Result<(), String> {
+    validate_config(&config).map_err(|errors| errors.join("；"))?;
     save_config_to_file(&config).map_err(|e| e.to_string())?;
     apply_windows_autostart(config.ui.autostart, config.ui.silent_start)?;
     Ok(())
 }
 
+// 修改单个配置字段，避免前端整份写回时覆盖其他地方的改动
+#[tauri::command]
+fn patch_config(path: String, value: serde_json::Value) -> Result<AppConfig, String> {
+    let config = config::patch_config(&path, value)?;
+    if matches!(path.as_str(), "ui.autostart" | "ui.silent_start") {
+        apply_windows_autostart(config.ui.autostart, config.ui.silent_start)?;
+    }
+    Ok(config)
+}
+
+// 导出完整配置，redact 为 true 时隐藏本机接口的 token
+#[tauri::command]
+fn export_config(redact: bool) -> Result<String, String> {
+    let config = if redact { load_redacted_config()? } else { load_config().map_err(|e| e.to_string())? };
+    serde_json::to_string_pretty(&config).map_err(|e| e.to_string())
+}
+
+fn load_redacted_config() -> Result<AppConfig, String> {
+    let mut config = load_config().map_err(|e| e.to_string())?;
+    if !config.local_api.token.is_empty() {
+        config.local_api.token = REDACTED_TOKEN.to_string();
+    }
+    Ok(config)
+}
+
+// 把配置、监听快照、最近的监听日志与各跟随文件的末尾写成一个 JSON 文件，便于排查误报；返回文件路径
+#[tauri::command]
+fn capture_diagnostics(app_handle: tauri::AppHandle, state: State<AppState>) -> Result<String, String> {
+    let now = chrono::Local::now();
+    let watch = get_watch_debug(state);
+    let sessions = watch
+        .sources
+        .iter()
+        .flat_map(|source| {
+            source.files.iter().map(|file| SessionTail {
+                source: source.source.clone(),
+                path: file.path.clone(),
+                lines: watch_log::tail_lines(std::path::Path::new(&file.path), DIAGNOSTICS_SESSION_LINES),
+            })
+        })
+        .collect();
+    let diagnostics = Diagnostics {
+        captured_at: now.to_rfc3339(),
+        os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+        meta: get_meta(app_handle),
+        config: load_redacted_config()?,
+        watch_log: watch_log::tail_lines(&watch_log::get_watch_log_path(now.date_naive()), DIAGNOSTICS_LOG_LINES),
+        watch,
+        sessions,
+    };
+
+    let dir = get_data_dir().join("diagnostics");
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("diagnostics-{}.json", now.format("%Y%m%d-%H%M%S")));
+    let content = serde_json::to_string_pretty(&diagnostics).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| e.to_string())?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+// 缺失的字段按默认值补齐，旧版本配置先经过迁移
+#[tauri::command]
+fn import_config(json: String) -> Result<(), String> {
+    let value: serde_json::Value = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+    let mut config = migrate_config(value).map_err(|e| e.to_string())?;
+    // 导入脱敏导出的配置时保留本机现有 token
+    if config.local_api.token == REDACTED_TOKEN {
+        config.local_api.token = load_config().map(|c| c.local_api.token).unwrap_or_default();
+    }
+    save_config(config)
+}
+
 #[tauri::command]
 fn watch_status(state: State<AppState>) -> WatchStatus {
     let guard = state.watch_stop.lock().unwrap_or_else(|e| e.into_inner());
-    WatchStatus { running: guard.is_some() }
+    WatchStatus {
+        running: guard.is_some(),
+        paused: state.watch_paused.load(Ordering::Relaxed),
+    }
+}
+
+// 监听未运行时不返回上一次的跟随信息
+#[tauri::command]
+fn get_watch_debug(state: State<AppState>) -> watch::WatchDebugInfo {
+    let running = state.watch_stop.lock().unwrap_or_else(|e| e.into_inner()).is_some();
+    let sources = if running {
+        state.watch_debug.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    } else {
+        Vec::new()
+    };
+    watch::WatchDebugInfo {
+        running,
+        paused: state.watch_paused.load(Ordering::Relaxed),
+        sources,
+    }
+}
+
+#[tauri::command]
+fn test_confirm_detect(text: String) -> Result<Option<String>, String> {
+    watch::test_confirm_detect(&text)
+}
+
+#[tauri::command]
+fn test_turn_end_confirm(text: String) -> Option<String> {
+    watch::test_turn_end_confirm(&text)
+}
+
+#[tauri::command]
+fn get_health(state: State<AppState>) -> watch::WatchHealth {
+    let running = state.watch_stop.lock().unwrap_or_else(|e| e.into_inner()).is_some();
+    let stale_minutes = config::load_config().map(|c| c.ui.watch_stale_minutes).unwrap_or_default();
+    let now = chrono::Local::now().timestamp_millis();
+    let sources = if running {
+        let snapshot = state.watch_debug.lock().unwrap_or_else(|e| e.into_inner());
+        snapshot
+            .iter()
+            .filter(|s| s.running)
+            .map(|s| watch::source_health(s, now, i64::from(stale_minutes) * 60_000))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    watch::WatchHealth {
+        running,
+        paused: state.watch_paused.load(Ordering::Relaxed),
+        stale_minutes,
+        sources,
+    }
+}
+
+// 返回当前跟随的（最近修改的）会话文件中最后 lines 条对话；之后新读到的内容以 session-tail 事件推送
+#[tauri::command]
+fn tail_current_session(source: String, lines: usize, state: State<AppState>) -> Result<Vec<watch::TranscriptEntry>, String> {
+    if state.watch_stop.lock().unwrap_or_else(|e| e.into_inner()).is_none() {
+        return Err("Watch not running".to_string());
+    }
+    let path = {
+        let snapshot = state.watch_debug.lock().unwrap_or_else(|e| e.into_inner());
+        snapshot
+            .iter()
+            .find(|s| s.source == source)
+            .and_then(|s| s.files.iter().max_by_key(|f| f.modified_at))
+            .map(|f| std::path::PathBuf::from(&f.path))
+            .ok_or_else(|| format!("{} 当前没有跟随的会话文件", source))?
+    };
+    watch::tail_session_file(&source, &path, lines)
 }
 
 #[tauri::command]
 async fn start_watch(payload: WatchStartPayload, app: tauri::AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    let app_handle = app.clone();
     let mut guard = state.watch_stop.lock().unwrap_or_else(|e| e.into_inner());
     if guard.is_some() {
         return Err("Watch already running".to_string());
     }
+    state.watch_paused.store(false, Ordering::Relaxed);
 
     let stop = start_watch_fn(
         &payload.sources,
         payload.interval_ms,
         payload.gemini_quiet_ms,
         payload.claude_quiet_ms,
-        move |line: String| {
-            let _ = app.emit("watch-log", line);
+        payload.codex_quiet_ms,
+        watch::WatchControl {
+            paused: state.watch_paused.clone(),
+            debug: state.watch_debug.clone(),
         },
+        watch_log_emitter(app),
     ).map_err(|e| e.to_string())?;
 
     *guard = Some(stop);
+    drop(guard);
+    refresh_tray(&app_handle);
     Ok(())
 }
 
 #[tauri::command]
-fn stop_watch(state: State<AppState>) -> Result<(), String> {
-    let mut guard = state.watch_stop.lock().unwrap_or_else(|e| e.into_inner());
-    if let Some(stop) = guard.take() {
-        stop();
-        Ok(())
-    } else {
-        Err("Watch not running".to_string())
+fn stop_watch(app: tauri::AppHandle, state: State<AppState>) -> Result<(), String> {
+    let stop = state.watch_stop.lock().unwrap_or_else(|e| e.into_inner()).take();
+    let Some(stop) = stop else {
+        return Err("Watch not running".to_string());
+    };
+    stop.stop();
+    state.watch_paused.store(false, Ordering::Relaxed);
+    refresh_tray(&app);
+    Ok(())
+}
+
+// 返回设置后的暂停状态；监听循环在每次 tick 后检查该标记。监听未运行时报错，
+// 避免暂停标记残留到下一次启动
+#[tauri::command]
+fn set_watch_paused(paused: bool, app: tauri::AppHandle, state: State<AppState>) -> Result<bool, String> {
+    if state.watch_stop.lock().unwrap_or_else(|e| e.into_inner()).is_none() {
+        return Err("Watch not running".to_string());
+    }
+    state.watch_paused.store(paused, Ordering::Relaxed);
+    refresh_tray(&app);
+    Ok(state.watch_paused.load(Ordering::Relaxed))
+}
+
+// 用系统默认的文本编辑器打开 settings.json，文件不存在时先写入默认配置
+#[tauri::command]
+fn open_config_in_editor() -> Result<String, String> {
+    let path = get_config_path();
+    if !path.exists() {
+        save_config_to_file(&AppConfig::default()).map_err(|e| e.to_string())?;
+    }
+
+    #[cfg(target_os = "windows")]
+    let mut command = std::process::Command::new("notepad");
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        let mut command = std::process::Command::new("open");
+        command.arg("-t");
+        command
+    };
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut command = std::process::Command::new("xdg-open");
+
+    command.arg(&path).spawn().map_err(|e| e.to_string())?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+// 手动编辑后重新读取；监控会自行检测文件变更，这里只负责把结果交给界面
+#[tauri::command]
+fn reload_config() -> Result<AppConfig, String> {
+    reload_config_from_file()
+}
+
+#[tauri::command]
+fn detect_sources() -> Vec<watch::SourceDetection> {
+    watch::detect_sources()
+}
+
+// date 为 YYYY-MM-DD，省略时打开今天的日志（不存在则创建空文件）；指定日期没有日志时报错
+#[tauri::command]
+fn open_watch_log(date: Option<String>) -> Result<String, String> {
+    let path = match date.as_deref().map(str::trim).filter(|d| !d.is_empty()) {
+        Some(date) => {
+            let date = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|_| format!("日期格式应为 YYYY-MM-DD：{}", date))?;
+            let path = watch_log::get_watch_log_path(date);
+            if !path.exists() {
+                return Err(format!("{} 没有监听日志", date.format("%Y-%m-%d")));
+            }
+            path
+        }
+        None => {
+            let path = watch_log::get_watch_log_path(chrono::Local::now().date_naive());
+            if !path.exists() {
+                std::fs::create_dir_all(watch_log::get_watch_log_dir()).map_err(|e| e.to_string())?;
+                std::fs::File::create(&path).map_err(|e| e.to_string())?;
+            }
+            path
+        }
+    };
+    open_with_system(&path)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+fn list_watch_logs() -> Vec<String> {
+    watch_log::list_log_dates()
+}
+
+// 回放录制的会话文件，speed 为时间倍速（2.0 表示两倍速）
+#[tauri::command]
+async fn replay_log(source: String, path: String, speed: f64) -> Result<usize, String> {
+    watch::replay_log(source.trim(), std::path::Path::new(&path), speed).await
+}
+
+#[tauri::command]
+fn clear_watch_logs() -> Result<ClearedWatchLogs, String> {
+    let (removed, skipped) = watch_log::clear_watch_logs();
+    Ok(ClearedWatchLogs { removed, skipped })
+}
+
+#[tauri::command]
+fn get_notification_history(limit: usize, state: State<AppState>) -> Vec<notify::NotificationRecord> {
+    state.notification_history.recent(limit)
+}
+
+// 静音状态变化后刷新托盘提示，并通知前端
+fn update_snooze_indicator(app: &tauri::AppHandle, status: &notify::SnoozeStatus) {
+    refresh_tray(app);
+    let _ = app.emit("snooze-changed", status.clone());
+}
+
+#[tauri::command]
+fn snooze(minutes: i32, app: tauri::AppHandle, state: State<AppState>) -> Result<notify::SnoozeStatus, String> {
+    if minutes <= 0 {
+        return Err("静音时长必须大于 0 分钟".to_string());
     }
+    let now = chrono::Local::now().timestamp_millis();
+    state.snooze.set(Some(now + minutes as i64 * 60_000)).map_err(|e| e.to_string())?;
+    let status = state.snooze.status(now);
+    update_snooze_indicator(&app, &status);
+    Ok(status)
+}
+
+#[tauri::command]
+fn cancel_snooze(app: tauri::AppHandle, state: State<AppState>) -> Result<notify::SnoozeStatus, String> {
+    state.snooze.set(None).map_err(|e| e.to_string())?;
+    let status = state.snooze.status(chrono::Local::now().timestamp_millis());
+    update_snooze_indicator(&app, &status);
+    Ok(status)
+}
+
+#[tauri::command]
+fn get_snooze_status(state: State<AppState>) -> notify::SnoozeStatus {
+    state.snooze.status(chrono::Local::now().timestamp_millis())
+}
+
+#[tauri::command]
+fn get_stats(days: i32) -> stats::StatsSummary {
+    stats::get_stats(days)
 }
 
 #[tauri::command]
@@ -195,39 +582,149 @@ async fn test_notification(payload: TestNotifyPayload) -> Result<(), String> {
     Ok(())
 }
 
+#[tauri::command]
+async fn test_channel(channel: String) -> Result<serde_json::Value, String> {
+    notify::send_channel_test(&channel).await
+}
+
+#[tauri::command]
+async fn test_all_channels(source: String) -> Result<Vec<notify::ChannelTestResult>, String> {
+    notify::test_all_channels(&source).await
+}
+
+// 保存需要动态更新文字的托盘菜单项
+struct TrayMenuState {
+    toggle_watch: MenuItem<tauri::Wry>,
+}
+
+// 按监听与静音状态刷新托盘菜单文字和提示
+fn refresh_tray(app: &tauri::AppHandle) {
+    let Some(state) = app.try_state::<AppState>() else { return; };
+    let running = state.watch_stop.lock().unwrap_or_else(|e| e.into_inner()).is_some();
+    let paused = running && state.watch_paused.load(Ordering::Relaxed);
+
+    if let Some(menu) = app.try_state::<TrayMenuState>() {
+        let _ = menu.toggle_watch.set_text(if running && !paused { "暂停监控" } else { "恢复监控" });
+    }
+
+    let status = if !running { "监控已停止" } else if paused { "监控已暂停" } else { "监控运行中" };
+    let mut tooltip = format!("Aitify · {}", status);
+    let snooze_until = state
+        .snooze
+        .status(chrono::Local::now().timestamp_millis())
+        .until
+        .and_then(chrono::DateTime::from_timestamp_millis);
+    if let Some(until) = snooze_until {
+        tooltip.push_str(&format!(" · 静音至 {}", until.with_timezone(&chrono::Local).format("%H:%M")));
+    }
+    if let Some(tray) = app.try_state::<tauri::tray::TrayIcon>() {
+        let _ = tray.set_tooltip(Some(tooltip));
+    }
+}
+
+// 托盘切换监听：未运行时启动，运行中时切换暂停
+fn toggle_watch_from_tray(app: &tauri::AppHandle) {
+    let state = app.state::<AppState>();
+    let running = state.watch_stop.lock().unwrap_or_else(|e| e.into_inner()).is_some();
+    if !running {
+        if let Err(e) = start_watch_default(app, &state) {
+            eprintln!("Failed to start watch from tray: {}", e);
+        }
+    } else {
+        let paused = !state.watch_paused.load(Ordering::Relaxed);
+        state.watch_paused.store(paused, Ordering::Relaxed);
+    }
+    refresh_tray(app);
+    let _ = app.emit("watch-status-changed", ());
+}
+
+// 显示主窗口时恢复任务栏图标，隐藏到托盘时一并从任务栏移除
+// 第二个实例启动、托盘打开与通知聚焦共用。从托盘隐藏（skip_taskbar）恢复时
+// 一次 set_focus 有时不生效，未获得焦点时按 50 / 100 / 200ms 退避重试
+fn show_main_window(app: &tauri::AppHandle) {
+    let Some(window) = app.get_webview_window("main") else { return; };
+    if window.is_minimized().unwrap_or(false) {
+        let _ = window.unminimize();
+    }
+    let _ = window.set_skip_taskbar(false);
+    let _ = window.show();
+    let _ = window.set_focus();
+
+    tauri::async_runtime::spawn(async move {
+        for delay_ms in [50, 100, 200] {
+            tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+            if window.is_focused().unwrap_or(true) {
+                return;
+            }
+            let _ = window.set_focus();
+        }
+    });
+}
+
+fn hide_to_tray(window: &tauri::WebviewWindow) {
+    let _ = window.hide();
+    let _ = window.set_skip_taskbar(true);
+}
+
+// 托盘左键与“打开”菜单共用，按 ui.tray_click_behavior 处理
+fn handle_tray_open(app: &tauri::AppHandle) {
+    let behavior = load_config().map(|c| c.ui.tray_click_behavior).unwrap_or_default();
+    match behavior.as_str() {
+        "none" => {}
+        "toggle" => match app.get_webview_window("main") {
+            Some(window) if window.is_visible().unwrap_or(false) => hide_to_tray(&window),
+            _ => show_main_window(app),
+        },
+        _ => show_main_window(app),
+    }
+}
+
+// 部分开发构建没有打包窗口图标，用主题色方块兜底，保证托盘仍然出现
+fn fallback_tray_icon() -> tauri::image::Image<'static> {
+    const SIZE: u32 = 32;
+    let rgba = [0x4f, 0x46, 0xe5, 0xff].repeat((SIZE * SIZE) as usize);
+    tauri::image::Image::new_owned(rgba, SIZE, SIZE)
+}
+
 fn setup_tray(app: &tauri::AppHandle) -> Result<(), Box<dyn std::error::Error>> {
     use tauri::menu::PredefinedMenuItem;
 
     let open_i = MenuItem::with_id(app, "open", "打开", true, None::<&str>)?;
+    let toggle_watch_i = MenuItem::with_id(app, "toggle_watch", "恢复监控", true, None::<&str>)?;
+    let test_i = MenuItem::with_id(app, "test_notification", "测试通知", true, None::<&str>)?;
     let quit_i = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
     let separator = PredefinedMenuItem::separator(app)?;
-    let menu = Menu::with_items(app, &[&open_i, &separator, &quit_i])?;
+    let menu = Menu::with_items(app, &[&open_i, &toggle_watch_i, &test_i, &separator, &quit_i])?;
 
-    let tray_icon = app
-        .default_window_icon()
-        .cloned()
-        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "default window icon not found"))?;
+    let tray_icon = match app.default_window_icon().cloned() {
+        Some(icon) => icon,
+        None => {
+            eprintln!("Default window icon not found, using fallback tray icon");
+            fallback_tray_icon()
+        }
+    };
 
+    // 切换模式下左键只用来显示 / 隐藏窗口，菜单改为右键打开
+    let toggle_on_click = load_config().is_ok_and(|c| c.ui.tray_click_behavior == "toggle");
     let tray = TrayIconBuilder::new()
         .icon(tray_icon)
         .menu(&menu)
-        .show_menu_on_left_click(true)
+        .show_menu_on_left_click(!toggle_on_click)
         .on_tray_icon_event(|tray, event| {
-            if let TrayIconEvent::Click { button: MouseButton::Left, .. } = event {
-                let app = tray.app_handle();
-                if let Some(window) = app.get_webview_window("main") {
-                    let _ = window.show();
-                    let _ = window.set_focus();
-                }
+            if let TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. } = event {
+                handle_tray_open(tray.app_handle());
             }
         })
         .on_menu_event(|app, event| {
             match event.id.as_ref() {
-                "open" => {
-                    if let Some(window) = app.get_webview_window("main") {
-                        let _ = window.show();
-                        let _ = window.set_focus();
-                    }
+                "open" => handle_tray_open(app),
+                "toggle_watch" => toggle_watch_from_tray(app),
+                "test_notification" => {
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = send_notifications("claude", "这是一条测试通知", None, String::new(), true, None).await {
+                            eprintln!("Failed to send test notification from tray: {}", e);
+                        }
+                    });
                 }
                 "quit" => {
                     if let Some(window) = app.get_webview_window("main") {
@@ -241,6 +738,7 @@ fn setup_tray(app: &tauri::AppHandle) -> Result<(), Box<dyn std::error::Error>>
         .build(app)?;
 
     app.manage(tray);
+    app.manage(TrayMenuState { toggle_watch: toggle_watch_i });
     Ok(())
 }
 
@@ -251,47 +749,48 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
-            if let Some(window) = app.get_webview_window("main") {
-                let _ = window.show();
-                let _ = window.set_focus();
-            }
+            show_main_window(app);
         }))
         .manage(AppState::default())
         .setup(|app| {
+            notify::set_app_handle(app.handle().clone());
             setup_tray(app.handle())?;
 
             // 监听窗口关闭事件，隐藏到托盘而不是退出
             if let Some(window) = app.get_webview_window("main") {
                 let window_clone = window.clone();
-                window.on_window_event(move |event| {
-                    if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                window.on_window_event(move |event| match event {
+                    tauri::WindowEvent::CloseRequested { api, .. } => {
                         // 阻止默认关闭行为
                         api.prevent_close();
                         // 隐藏窗口
-                        let _ = window_clone.hide();
+                        hide_to_tray(&window_clone);
+                    }
+                    tauri::WindowEvent::Focused(focused) => {
+                        window_clone.state::<AppState>().window_focused.store(*focused, Ordering::Relaxed);
                     }
+                    _ => {}
                 });
             }
 
-            // 仅当由开机自启命令行参数触发时才静默隐藏。
             let config = load_config().unwrap_or_else(|_| AppConfig::default());
-            let launched_with_silent = std::env::args().any(|arg| arg == AUTOSTART_SILENT_ARG);
-            let should_show = !launched_with_silent && !config.ui.silent_start;
+            let startup_mode = get_startup_mode(config.ui.silent_start, std::env::args());
             if let Err(e) = apply_windows_autostart(config.ui.autostart, config.ui.silent_start) {
                 eprintln!("Failed to apply autostart: {}", e);
             }
+            watch_log::cleanup_watch_logs(config.ui.watch_log_retention_days);
+            local_api::start_local_api(&config.local_api);
             let app_state = app.state::<AppState>();
             if let Err(e) = start_watch_default(app.handle(), &app_state) {
                 eprintln!("Failed to start watch by default: {}", e);
             }
+            // 同时反映重启前仍在有效期内的静音
+            refresh_tray(app.handle());
 
-            if let Some(window) = app.get_webview_window("main") {
-                if should_show {
-                    let _ = window.show();
-                    let _ = window.set_focus();
-                } else {
-                    let _ = window.hide();
-                }
+            if startup_mode == StartupMode::Shown {
+                show_main_window(app.handle());
+            } else if let Some(window) = app.get_webview_window("main") {
+                hide_to_tray(&window);
             }
 
             Ok(())
@@ -300,10 +799,34 @@ pub fn run() {
             get_meta,
             get_config,
             save_config,
+            open_config_in_editor,
+            reload_config,
+            export_config,
+            import_config,
             watch_status,
+            get_watch_debug,
+            get_health,
+            tail_current_session,
+            patch_config,
+            test_confirm_detect,
+            test_turn_end_confirm,
+            capture_diagnostics,
+            detect_sources,
             start_watch,
             stop_watch,
+            set_watch_paused,
+            open_watch_log,
+            list_watch_logs,
+            clear_watch_logs,
+            replay_log,
+            get_notification_history,
+            snooze,
+            cancel_snooze,
+            get_snooze_status,
+            get_stats,
             test_notification,
+            test_channel,
+            test_all_channels,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -312,3 +835,15 @@ pub fn run() {
 fn main() {
     run();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_startup_mode_honors_config_and_silent_arg() {
+        assert_eq!(get_startup_mode(false, ["aitify"]), StartupMode::Shown);
+        assert_eq!(get_startup_mode(true, ["aitify"]), StartupMode::Minimized);
+        assert_eq!(get_startup_mode(false, ["aitify", AUTOSTART_SILENT_ARG]), StartupMode::Minimized);
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+// 本机接口 GET /metrics 输出的计数，进程重启后清零
+#[derive(Default)]
+struct Metrics {
+    // (source, channel, result) -> 次数
+    notifications: BTreeMap<(String, String, String), u64>,
+    confirms: BTreeMap<String, u64>,
+    // source -> 是否正在监听
+    watch_running: BTreeMap<String, bool>,
+}
+
+static METRICS: Mutex<Metrics> = Mutex::new(Metrics {
+    notifications: BTreeMap::new(),
+    confirms: BTreeMap::new(),
+    watch_running: BTreeMap::new(),
+});
+
+fn with_metrics<T>(f: impl FnOnce(&mut Metrics) -> T) -> T {
+    let mut metrics = METRICS.lock().unwrap_or_else(|e| e.into_inner());
+    f(&mut metrics)
+}
+
+pub fn record_notification(source: &str, channel: &str, result: &str) {
+    with_metrics(|m| {
+        *m.notifications
+            .entry((source.to_string(), channel.to_string(), result.to_string()))
+            .or_default() += 1;
+    });
+}
+
+pub fn record_confirm(source: &str) {
+    with_metrics(|m| *m.confirms.entry(source.to_string()).or_default() += 1);
+}
+
+// 监听循环每轮更新；停止后把所有来源置为 0
+pub fn set_watch_running(sources: &[(&str, bool)]) {
+    with_metrics(|m| {
+        for (source, running) in sources {
+            m.watch_running.insert(source.to_string(), *running);
+        }
+    });
+}
+
+pub fn clear_watch_running() {
+    with_metrics(|m| m.watch_running.values_mut().for_each(|running| *running = false));
+}
+
+// Prometheus 文本格式
+pub fn render() -> String {
+    with_metrics(|m| render_metrics(m))
+}
+
+fn render_metrics(m: &Metrics) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# HELP aitify_notifications_total Notifications handled per source, channel and result.");
+    let _ = writeln!(out, "# TYPE aitify_notifications_total counter");
+    for ((source, channel, result), count) in &m.notifications {
+        let _ = writeln!(
+            out,
+            "aitify_notifications_total{{source=\"{}\",channel=\"{}\",result=\"{}\"}} {}",
+            escape_label(source),
+            escape_label(channel),
+            escape_label(result),
+            count
+        );
+    }
+    let _ = writeln!(out, "# HELP aitify_confirms_total Confirm prompts detected per source.");
+    let _ = writeln!(out, "# TYPE aitify_confirms_total counter");
+    for (source, count) in &m.confirms {
+        let _ = writeln!(out, "aitify_confirms_total{{source=\"{}\"}} {}", escape_label(source), count);
+    }
+    let _ = writeln!(out, "# HELP aitify_watch_running Whether the source is currently watched.");
+    let _ = writeln!(out, "# TYPE aitify_watch_running gauge");
+    for (source, running) in &m.watch_running {
+        let _ = writeln!(out, "aitify_watch_running{{source=\"{}\"}} {}", escape_label(source), u8::from(*running));
+    }
+    out
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_metrics_uses_prometheus_text_format() {
+        let mut metrics = Metrics::default();
+        metrics.notifications.insert(("claude".to_string(), "desktop".to_string(), "ok".to_string()), 2);
+        metrics.confirms.insert("my \"ci\"".to_string(), 1);
+        metrics.watch_running.insert("codex".to_string(), true);
+
+        let text = render_metrics(&metrics);
+        assert!(text.contains("# TYPE aitify_notifications_total counter\n"));
+        assert!(text.contains("aitify_notifications_total{source=\"claude\",channel=\"desktop\",result=\"ok\"} 2\n"));
+        assert!(text.contains("aitify_confirms_total{source=\"my \\\"ci\\\"\"} 1\n"));
+        assert!(text.contains("aitify_watch_running{source=\"codex\"} 1\n"));
+    }
+}
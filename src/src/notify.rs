@@ -1,8 +1,345 @@
+use serde::Serialize;
 use serde_json::json;
-use crate::config::{AppConfig, load_config};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex, OnceLock};
+use tauri::{Emitter, Manager};
+use crate::watch::{emit_watch_event, WatchEvent};
+use chrono::{Local, NaiveTime};
+use crate::i18n::{duration_style, format_duration, notify_strings, NotifyStrings};
+use crate::pricing::{estimate_cost, TokenUsage};
+use crate::turn_log::{record_turn, TurnRecord};
+use crate::config::{get_data_dir, AppConfig, QuietHoursConfig, SourceConfig, UiConfig, load_config};
 
 const PRIMARY_APP_ID: &str = "com.aitify.desktop";
 const LEGACY_APP_ID: &str = "Aitify.Notify";
+const SUMMARY_MAX_CHARS: usize = 150;
+const DESKTOP_MAX_MESSAGE_CHARS: usize = 200;
+const MAX_NOTIFICATION_HISTORY: usize = 200;
+
+// 非 Windows 平台通过 tauri_plugin_notification 发送（Windows 上作为 toast 全部失败后的兜底），
+// 需要在 setup 阶段保存 AppHandle
+static APP_HANDLE: OnceLock<tauri::AppHandle> = OnceLock::new();
+
+pub fn set_app_handle(handle: tauri::AppHandle) {
+    let _ = APP_HANDLE.set(handle);
+}
+
+// 没有 AppHandle（如单元测试）时直接丢弃
+pub fn emit_to_frontend<S: Serialize + Clone>(event: &str, payload: S) {
+    if let Some(app) = APP_HANDLE.get() {
+        let _ = app.emit(event, payload);
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChannelOutcome {
+    pub channel: String,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationRecord {
+    pub timestamp: i64,
+    pub source: String,
+    pub notification_type: String,
+    pub task_info: String,
+    pub channels: Vec<ChannelOutcome>,
+}
+
+// 完成与确认通知各自的上次发送时间，确认提醒不会压掉随后真正的完成通知
+#[derive(Default)]
+pub struct NotifyCooldown {
+    buckets: Mutex<HashMap<String, i64>>,
+}
+
+impl NotifyCooldown {
+    // 距同一 bucket 上次发送不足 cooldown_ms 时返回 false，否则记录本次时间
+    pub fn try_acquire(&self, bucket: &str, now: i64, cooldown_ms: i64) -> bool {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(last) = buckets.get(bucket) {
+            if now - *last < cooldown_ms {
+                return false;
+            }
+        }
+        buckets.insert(bucket.to_string(), now);
+        true
+    }
+}
+
+// 每个来源上一条完成通知的归一化内容与时间，用于合并同一回复被拆成多条记录时的重复通知
+#[derive(Default)]
+pub struct CompletionDedupe {
+    last: Mutex<HashMap<String, (String, i64)>>,
+}
+
+impl CompletionDedupe {
+    // 窗口内与上一条内容相同时返回 true；否则记录本条并返回 false
+    pub fn is_duplicate(&self, source: &str, task_info: &str, now: i64, window_ms: i64) -> bool {
+        let key = normalize_dedupe_key(task_info);
+        let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((last_key, last_at)) = last.get(source) {
+            if *last_key == key && now - *last_at < window_ms {
+                return true;
+            }
+        }
+        last.insert(source.to_string(), (key, now));
+        false
+    }
+}
+
+// 忽略大小写、空白与标点差异
+fn normalize_dedupe_key(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+// 来源被禁用、未达到最短时长，或桌面通知因用户正在操作（窗口有焦点、最近有输入）而跳过时不抢焦点
+fn should_focus_after(desktop_result: &serde_json::Value) -> bool {
+    let filtered = matches!(
+        desktop_result.get("error").and_then(|v| v.as_str()),
+        Some("source disabled") | Some("below min duration")
+    );
+    let skipped = desktop_result.get("skipped").and_then(|v| v.as_bool()).unwrap_or(false);
+    !filtered && !skipped
+}
+
+// 首次回复（reply）使用单独的冷却 bucket，也不参与完成去重，
+// 避免 notify_mode 为 both 时紧随其后的完成通知被吞掉
+fn cooldown_bucket(notification_type: Option<&str>) -> &'static str {
+    match notification_type {
+        Some("confirm") => "confirm",
+        Some("reply") => "reply",
+        _ => "complete",
+    }
+}
+
+// 完成去重与冷却，返回跳过原因
+fn throttle_reason(
+    cooldown: &NotifyCooldown,
+    dedupe: &CompletionDedupe,
+    ui: &UiConfig,
+    source: &str,
+    task_info: &str,
+    notification_type: Option<&str>,
+    now: i64,
+) -> Option<&'static str> {
+    if notification_type == Some("complete")
+        && ui.completion_dedupe_ms > 0
+        && dedupe.is_duplicate(source, task_info, now, ui.completion_dedupe_ms)
+    {
+        return Some("duplicate");
+    }
+    if ui.notify_cooldown_ms > 0 && !cooldown.try_acquire(cooldown_bucket(notification_type), now, ui.notify_cooldown_ms) {
+        return Some("cooldown");
+    }
+    None
+}
+
+pub fn get_snooze_path() -> PathBuf {
+    get_data_dir().join("snooze.json")
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SnoozeStatus {
+    pub active: bool,
+    pub until: Option<i64>,
+    pub remaining_ms: i64,
+}
+
+// 临时静音所有通道的截止时间（毫秒时间戳），写入 snooze.json，重启后仍在有效期内时继续生效
+#[derive(Default)]
+pub struct Snooze {
+    until: Mutex<Option<i64>>,
+}
+
+impl Snooze {
+    pub fn load() -> Self {
+        let until = fs::read_to_string(get_snooze_path())
+            .ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+            .and_then(|value| value.get("until").and_then(|v| v.as_i64()))
+            .filter(|until| *until > Local::now().timestamp_millis());
+        Self { until: Mutex::new(until) }
+    }
+
+    // None 表示取消静音并删除持久化文件
+    pub fn set(&self, until: Option<i64>) -> std::io::Result<()> {
+        *self.until.lock().unwrap_or_else(|e| e.into_inner()) = until;
+        let path = get_snooze_path();
+        match until {
+            Some(until) => {
+                fs::create_dir_all(get_data_dir())?;
+                fs::write(path, json!({ "until": until }).to_string())
+            }
+            None if path.exists() => fs::remove_file(path),
+            None => Ok(()),
+        }
+    }
+
+    pub fn status(&self, now: i64) -> SnoozeStatus {
+        let until = *self.until.lock().unwrap_or_else(|e| e.into_inner());
+        match until.filter(|until| *until > now) {
+            Some(until) => SnoozeStatus { active: true, until: Some(until), remaining_ms: until - now },
+            None => SnoozeStatus { active: false, until: None, remaining_ms: 0 },
+        }
+    }
+}
+
+fn is_snoozed() -> bool {
+    let Some(app) = APP_HANDLE.get() else { return false; };
+    let Some(state) = app.try_state::<crate::AppState>() else { return false; };
+    state.snooze.status(Local::now().timestamp_millis()).active
+}
+
+struct DigestBucket {
+    count: u32,
+    total_duration_ms: Option<i64>,
+    task_info: String,
+    summary: String,
+    cwd: String,
+}
+
+// 按来源累积汇总窗口内的完成通知，窗口结束时由定时任务取出合并发送
+#[derive(Default)]
+pub struct CompletionDigest {
+    buckets: Mutex<HashMap<String, DigestBucket>>,
+}
+
+impl CompletionDigest {
+    // 返回 true 表示窗口内的第一条，调用方需要安排窗口结束时的发送
+    fn add(&self, source: &str, text: NotificationText, duration_ms: Option<i64>, cwd: &str) -> bool {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        match buckets.get_mut(source) {
+            Some(bucket) => {
+                bucket.count += 1;
+                if let Some(ms) = duration_ms {
+                    bucket.total_duration_ms = Some(bucket.total_duration_ms.unwrap_or(0) + ms);
+                }
+                bucket.cwd = cwd.to_string();
+                false
+            }
+            None => {
+                buckets.insert(
+                    source.to_string(),
+                    DigestBucket {
+                        count: 1,
+                        total_duration_ms: duration_ms,
+                        task_info: text.task_info.to_string(),
+                        summary: text.summary.to_string(),
+                        cwd: cwd.to_string(),
+                    },
+                );
+                true
+            }
+        }
+    }
+
+    fn take(&self, source: &str) -> Option<DigestBucket> {
+        self.buckets.lock().unwrap_or_else(|e| e.into_inner()).remove(source)
+    }
+}
+
+// 返回 true 表示已放入汇总，不再单独发送
+fn add_to_digest(source: &str, text: NotificationText, duration_ms: Option<i64>, cwd: &str, window_ms: i64) -> bool {
+    if window_ms <= 0 {
+        return false;
+    }
+    let Some(app) = APP_HANDLE.get() else { return false; };
+    let Some(state) = app.try_state::<crate::AppState>() else { return false; };
+    if !state.completion_digest.add(source, text, duration_ms, cwd) {
+        return true;
+    }
+
+    let source = source.to_string();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(window_ms as u64)).await;
+        let Some(app) = APP_HANDLE.get() else { return; };
+        let Some(state) = app.try_state::<crate::AppState>() else { return; };
+        let Some(bucket) = state.completion_digest.take(&source) else { return; };
+        let Ok(config) = load_config() else { return; };
+        // 窗口内只有一条时保持原样
+        let (task_info, summary) = if bucket.count > 1 {
+            let merged = notify_strings(&config.ui).format_tasks_completed(bucket.count);
+            (merged.clone(), merged)
+        } else {
+            (bucket.task_info, bucket.summary)
+        };
+        let text = NotificationText { task_info: &task_info, summary: &summary };
+        let _ = deliver_notifications(&config, &source, text, bucket.total_duration_ms, &bucket.cwd, false, Some("complete")).await;
+    });
+    true
+}
+
+// 最近通知记录的环形缓冲区，超过上限时丢弃最旧的记录
+#[derive(Clone, Default)]
+pub struct NotificationHistory {
+    records: Arc<Mutex<VecDeque<NotificationRecord>>>,
+}
+
+impl NotificationHistory {
+    pub fn push(&self, record: NotificationRecord) {
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        records.push_back(record);
+        while records.len() > MAX_NOTIFICATION_HISTORY {
+            records.pop_front();
+        }
+    }
+
+    // 按时间倒序返回最近 limit 条
+    pub fn recent(&self, limit: usize) -> Vec<NotificationRecord> {
+        let records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        records.iter().rev().take(limit).cloned().collect()
+    }
+}
+
+fn record_notification(source: &str, task_info: &str, notification_type: Option<&str>, results: &[serde_json::Value]) {
+    for result in results {
+        let channel = result.get("channel").and_then(|v| v.as_str()).unwrap_or_default();
+        crate::metrics::record_notification(source, channel, metrics_result(result));
+    }
+
+    let Some(app) = APP_HANDLE.get() else { return; };
+    let Some(state) = app.try_state::<crate::AppState>() else { return; };
+
+    let channels = results
+        .iter()
+        .map(|result| ChannelOutcome {
+            channel: result.get("channel").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+            ok: result.get("ok").and_then(|v| v.as_bool()).unwrap_or(false),
+            error: result.get("error").and_then(|v| v.as_str()).map(|e| e.to_string()),
+        })
+        .collect();
+
+    state.notification_history.push(NotificationRecord {
+        timestamp: Local::now().timestamp_millis(),
+        source: source.to_string(),
+        notification_type: notification_type.unwrap_or("complete").to_string(),
+        task_info: task_info.to_string(),
+        channels,
+    });
+}
+
+// 被配置过滤（通道关闭、来源禁用、未达最短时长、免打扰时段）的结果与主动跳过的一样记为 skipped
+fn metrics_result(result: &serde_json::Value) -> &'static str {
+    if result.get("ok").and_then(|v| v.as_bool()).unwrap_or(false) {
+        return "ok";
+    }
+    let filtered = matches!(
+        result.get("error").and_then(|v| v.as_str()),
+        Some("disabled") | Some("source disabled") | Some("below min duration") | Some("quiet hours")
+    );
+    if filtered || result.get("skipped").and_then(|v| v.as_bool()).unwrap_or(false) {
+        "skipped"
+    } else {
+        "error"
+    }
+}
 
 #[cfg(target_os = "windows")]
 fn register_app_id(app_id: &str) {
@@ -16,17 +353,140 @@ fn register_app_id(app_id: &str) {
     }
 }
 
+// 通知正文；summary 为助手回复摘录（不含标题与用量），供通知模板的 {summary} 使用，
+// 没有单独摘录的通知与 task_info 相同
+#[derive(Clone, Copy)]
+pub struct NotificationText<'a> {
+    pub task_info: &'a str,
+    pub summary: &'a str,
+}
+
+impl<'a> NotificationText<'a> {
+    pub fn plain(task_info: &'a str) -> Self {
+        Self { task_info, summary: task_info }
+    }
+}
+
 pub async fn send_notifications(
     source: &str,
     task_info: &str,
     duration_ms: Option<i64>,
-    _cwd: String,
+    cwd: String,
+    force: bool,
+    notification_type: Option<&str>,
+) -> Result<serde_json::Value, String> {
+    send_notification_text(source, NotificationText::plain(task_info), duration_ms, cwd, force, notification_type).await
+}
+
+async fn send_notification_text(
+    source: &str,
+    text: NotificationText<'_>,
+    duration_ms: Option<i64>,
+    cwd: String,
     force: bool,
     notification_type: Option<&str>,
 ) -> Result<serde_json::Value, String> {
+    let task_info = &sanitize_summary(text.task_info);
+    let summary = &sanitize_summary(text.summary);
+    let text = NotificationText { task_info, summary };
+    if !force && notification_type == Some("confirm") {
+        emit_watch_event(WatchEvent::Confirm {
+            source: source.to_string(),
+            prompt: task_info.to_string(),
+        });
+        crate::metrics::record_confirm(source);
+        let mut turn = TurnRecord::new(source, duration_ms, &cwd);
+        turn.was_confirm = true;
+        turn.summary = truncate_summary(task_info, SUMMARY_MAX_CHARS);
+        record_turn(&turn);
+    }
+
+    if !force && is_snoozed() {
+        return Ok(json!({
+            "skipped": true,
+            "reason": "snoozed",
+            "results": []
+        }));
+    }
+
     let config = load_config().map_err(|e| e.to_string())?;
-    let result = send_desktop(&config, source, task_info, &duration_ms, force, notification_type).await;
-    let ok = result.get("ok").and_then(|v| v.as_bool()).unwrap_or(false);
+    if !force && !cwd_filter_allows(&config.ui, &cwd) {
+        return Ok(json!({
+            "skipped": true,
+            "reason": "cwd filtered",
+            "results": []
+        }));
+    }
+    let throttled = APP_HANDLE
+        .get()
+        .and_then(|app| app.try_state::<crate::AppState>())
+        .filter(|_| !force)
+        .and_then(|state| {
+            let now = Local::now().timestamp_millis();
+            throttle_reason(&state.notify_cooldown, &state.completion_dedupe, &config.ui, source, task_info, notification_type, now)
+        });
+    if let Some(reason) = throttled {
+        return Ok(json!({
+            "skipped": true,
+            "reason": reason,
+            "results": []
+        }));
+    }
+    // 首次回复不计入统计，避免 both 模式下同一轮被算作两次完成
+    if !force && notification_type != Some("reply") {
+        crate::stats::record_stat(source, notification_type, duration_ms);
+    }
+    // 完成通知在汇总窗口内累积，窗口结束时合并为一条；确认与出错不参与汇总
+    if !force
+        && notification_type == Some("complete")
+        && add_to_digest(source, text, duration_ms, &cwd, config.ui.digest_window_ms)
+    {
+        return Ok(json!({
+            "skipped": true,
+            "reason": "digest",
+            "results": []
+        }));
+    }
+
+    deliver_notifications(&config, source, text, duration_ms, &cwd, force, notification_type).await
+}
+
+// 按免打扰设置发往各通道，并记录通知历史
+async fn deliver_notifications(
+    config: &AppConfig,
+    source: &str,
+    text: NotificationText<'_>,
+    duration_ms: Option<i64>,
+    cwd: &str,
+    force: bool,
+    notification_type: Option<&str>,
+) -> Result<serde_json::Value, String> {
+    let quiet_hours = !force && is_quiet_hours_now(&config.ui.quiet_hours);
+    let result = if quiet_hours && quiet_hours_suppresses(&config.ui.quiet_hours, "desktop") {
+        json!({ "channel": "desktop", "ok": false, "error": "quiet hours" })
+    } else if !force && config.ui.suppress_when_focused && is_main_window_focused() {
+        json!({ "channel": "desktop", "ok": false, "skipped": true, "reason": "window focused" })
+    } else if !force && crate::platform_idle::user_recently_active(config.ui.only_when_idle_seconds) {
+        json!({ "channel": "desktop", "ok": false, "skipped": true, "reason": "user active" })
+    } else {
+        send_desktop(config, source, text, &duration_ms, cwd, force, notification_type).await
+    };
+    // 与桌面通道一样记录免打扰跳过；未配置 hook 命令时不产生结果
+    let hook_result = if quiet_hours && quiet_hours_suppresses(&config.ui.quiet_hours, "hooks") {
+        (!hook_command(config, notification_type).is_empty())
+            .then(|| json!({ "channel": "hooks", "ok": false, "error": "quiet hours" }))
+    } else {
+        run_hook(config, source, text.task_info, duration_ms, cwd, force, notification_type)
+    };
+    if !force && !quiet_hours && notification_type == Some("complete") && should_focus_after(&result) {
+        focus_window_on_notify(&config.ui, cwd);
+    }
+    let mut results = vec![result.clone()];
+    results.extend(hook_result);
+    record_notification(source, text.task_info, notification_type, &results);
+
+    let ok = result.get("ok").and_then(|v| v.as_bool()).unwrap_or(false)
+        || result.get("skipped").and_then(|v| v.as_bool()).unwrap_or(false);
     let error_text = result
         .get("error")
         .and_then(|v| v.as_str())
@@ -37,6 +497,7 @@ pub async fn send_notifications(
         && error_text != "disabled"
         && error_text != "source disabled"
         && error_text != "below min duration"
+        && error_text != "quiet hours"
     {
         return Err(error_text.to_string());
     }
@@ -44,94 +505,509 @@ pub async fn send_notifications(
     Ok(json!({
         "skipped": false,
         "reason": null,
-        "results": [result]
+        "results": results
     }))
 }
 
-async fn send_desktop(
+// 完成通知：标题为 "{display_name} 任务已完成"（按通知语言），
+// 并按来源的 include_summary 设置把助手最后一条消息的摘录拼进 task_info
+pub async fn send_completion_notification(
+    source: &str,
+    display_name: &str,
+    summary: Option<String>,
+    usage: Option<TokenUsage>,
+    duration_ms: Option<i64>,
+    cwd: String,
+    had_tool_use: Option<bool>,
+) -> Result<serde_json::Value, String> {
+    let config = load_config().map_err(|e| e.to_string())?;
+    let strings = notify_strings(&config.ui);
+    let source_config = get_source_config(&config, source);
+    let mut turn = TurnRecord::new(source, duration_ms, &cwd);
+    turn.had_tool_use = had_tool_use;
+    turn.summary = summary.as_deref().and_then(|text| truncate_summary(text, SUMMARY_MAX_CHARS));
+    record_turn(&turn);
+    if source_config.notify_mode == "first_reply" {
+        return Ok(json!({ "ok": true, "skipped": true, "reason": "notify_mode" }));
+    }
+    let title = format!("{} {}", display_name, strings.complete_suffix);
+    let mut usage_parts = Vec::new();
+    if let Some(usage) = usage.as_ref() {
+        if source_config.show_token_usage {
+            usage_parts.push(format!("{} tokens", format_token_count(usage.total_tokens())));
+        }
+        let cost = usage
+            .model
+            .as_deref()
+            .filter(|_| source_config.show_cost)
+            .and_then(|model| estimate_cost(model, usage, &config.pricing));
+        if let Some(cost) = cost {
+            usage_parts.push(format_cost(cost));
+        }
+    }
+    let title = if usage_parts.is_empty() {
+        title
+    } else {
+        format!("{}（{}）", title, usage_parts.join("，"))
+    };
+    let summary = summary
+        .filter(|_| source_config.include_summary)
+        .and_then(|text| truncate_summary(&text, SUMMARY_MAX_CHARS));
+    emit_watch_event(WatchEvent::Completion {
+        source: source.to_string(),
+        duration_ms,
+        summary: summary.clone(),
+    });
+    let task_info = match &summary {
+        Some(summary) => format!("{}{}{}", title, strings.separator, summary),
+        None => title,
+    };
+    let text = NotificationText { task_info: &task_info, summary: summary.as_deref().unwrap_or_default() };
+    send_notification_text(source, text, duration_ms, cwd, false, Some("complete")).await
+}
+
+// notify_mode 为 first_reply / both 时，用户消息之后的第一条助手回复立即通知，不经过去抖
+pub async fn send_first_reply_notification(
+    source: &str,
+    display_name: &str,
+    summary: Option<String>,
+    duration_ms: Option<i64>,
+    cwd: String,
+) -> Result<serde_json::Value, String> {
+    let config = load_config().map_err(|e| e.to_string())?;
+    let source_config = get_source_config(&config, source);
+    if !matches!(source_config.notify_mode.as_str(), "first_reply" | "both") {
+        return Ok(json!({ "ok": true, "skipped": true, "reason": "notify_mode" }));
+    }
+    let strings = notify_strings(&config.ui);
+    let title = format!("{} {}", display_name, strings.reply_suffix);
+    let summary = summary
+        .filter(|_| source_config.include_summary)
+        .and_then(|text| truncate_summary(&text, SUMMARY_MAX_CHARS));
+    let task_info = match &summary {
+        Some(summary) => format!("{}{}{}", title, strings.separator, summary),
+        None => title,
+    };
+    let text = NotificationText { task_info: &task_info, summary: summary.as_deref().unwrap_or_default() };
+    send_notification_text(source, text, duration_ms, cwd, false, Some("reply")).await
+}
+
+fn format_cost(cost: f64) -> String {
+    if cost < 0.01 {
+        "< $0.01".to_string()
+    } else {
+        format!("≈ ${:.2}", cost)
+    }
+}
+
+fn format_token_count(tokens: i64) -> String {
+    if tokens >= 1_000_000 {
+        format!("{:.1}M", tokens as f64 / 1_000_000.0)
+    } else if tokens >= 1_000 {
+        format!("{}k", tokens / 1_000)
+    } else {
+        tokens.to_string()
+    }
+}
+
+fn is_main_window_focused() -> bool {
+    let Some(app) = APP_HANDLE.get() else { return false; };
+    let Some(state) = app.try_state::<crate::AppState>() else { return false; };
+    state.window_focused.load(std::sync::atomic::Ordering::Relaxed)
+}
+
+// 路径统一为小写和正斜杠后比较，Windows 与类 Unix 的写法都能命中
+fn cwd_matches(cwd: &str, entry: &str) -> bool {
+    let normalize = |s: &str| s.trim().replace('\\', "/").to_lowercase();
+    let (cwd, entry) = (normalize(cwd), normalize(entry));
+    if entry.is_empty() {
+        return false;
+    }
+    if entry.contains(['*', '?', '[']) {
+        glob::Pattern::new(&entry).is_ok_and(|pattern| pattern.matches(&cwd))
+    } else {
+        cwd.contains(&entry)
+    }
+}
+
+// 工作目录未知（如 Gemini 的会话记录不含 cwd）时无法判断，不做过滤
+fn cwd_filter_allows(ui: &UiConfig, cwd: &str) -> bool {
+    if cwd.trim().is_empty() {
+        return true;
+    }
+    if ui.cwd_blocklist.iter().any(|entry| cwd_matches(cwd, entry)) {
+        return false;
+    }
+    ui.cwd_allowlist.is_empty() || ui.cwd_allowlist.iter().any(|entry| cwd_matches(cwd, entry))
+}
+
+// auto 先尝试切到 CLI 所在的终端窗口（目前仅 Windows），找不到时与 main 一样聚焦主窗口
+fn focus_window_on_notify(ui: &UiConfig, cwd: &str) {
+    if !ui.auto_focus_on_notify || ui.focus_target == "none" {
+        return;
+    }
+    if ui.focus_target == "auto" && crate::platform_focus::focus_terminal(cwd) {
+        return;
+    }
+    let Some(app) = APP_HANDLE.get() else { return; };
+
+    crate::show_main_window(app);
+    if ui.force_maximize_on_focus {
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.maximize();
+        }
+    }
+}
+
+// 出错通知：task_info 为标题加错误信息摘录
+pub async fn send_error_notification(
+    source: &str,
+    display_name: &str,
+    message: &str,
+    duration_ms: Option<i64>,
+    cwd: String,
+) -> Result<serde_json::Value, String> {
+    emit_watch_event(WatchEvent::Error {
+        source: source.to_string(),
+        message: message.to_string(),
+    });
+    let strings = notify_strings(&load_config().unwrap_or_default().ui);
+    let title = format!("{} {}", display_name, strings.error_suffix);
+    let mut turn = TurnRecord::new(source, duration_ms, &cwd);
+    turn.was_error = true;
+    turn.summary = truncate_summary(message, SUMMARY_MAX_CHARS);
+    record_turn(&turn);
+    let task_info = match truncate_summary(message, SUMMARY_MAX_CHARS) {
+        Some(excerpt) => format!("{}{}{}", title, strings.separator, excerpt),
+        None => title,
+    };
+    send_notifications(source, &task_info, duration_ms, cwd, false, Some("error")).await
+}
+
+// 去掉终端颜色等 ANSI 转义，把 3 个及以上连续换行压缩为 2 个并去掉首尾空白
+fn sanitize_summary(text: &str) -> String {
+    static ANSI: OnceLock<regex::Regex> = OnceLock::new();
+    static BLANK_LINES: OnceLock<regex::Regex> = OnceLock::new();
+    let ansi = ANSI.get_or_init(|| regex::Regex::new(r"\x1b\[[0-9;]*m").expect("valid ansi regex"));
+    let blank_lines = BLANK_LINES.get_or_init(|| regex::Regex::new(r"\n[ \t\r]*(?:\n[ \t\r]*){2,}").expect("valid blank line regex"));
+    let stripped = ansi.replace_all(text, "");
+    blank_lines.replace_all(&stripped, "\n\n").trim().to_string()
+}
+
+// 合并空白后按字符截断，避免切断多字节字符
+fn truncate_summary(text: &str, max_chars: usize) -> Option<String> {
+    let collapsed = sanitize_summary(text).split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.is_empty() || max_chars == 0 {
+        return None;
+    }
+    if collapsed.chars().count() <= max_chars {
+        return Some(collapsed);
+    }
+    let mut truncated: String = collapsed.chars().take(max_chars - 1).collect();
+    truncated.push('…');
+    Some(truncated)
+}
+
+// 各通道共用的消息截断：超出 max_chars 时按字符截断并补省略号，
+// 截断点之前的后半段里有换行时优先在最后一个换行处截断
+fn clamp_message(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    if max_chars == 0 {
+        return String::new();
+    }
+    let kept: String = text.chars().take(max_chars - 1).collect();
+    let kept = match kept.rfind('\n') {
+        Some(pos) if kept[..pos].chars().count() >= max_chars / 2 => kept[..pos].trim_end(),
+        _ => kept.as_str(),
+    };
+    format!("{}…", kept)
+}
+
+// 单独测试某个通道，返回该通道自身的结果
+const TEST_CHANNELS: [&str; 2] = ["desktop", "hooks"];
+
+pub async fn send_channel_test(channel: &str) -> Result<serde_json::Value, String> {
+    let config = load_config().map_err(|e| e.to_string())?;
+    let task_info = "这是一条测试通知";
+    match channel {
+        "desktop" => Ok(send_desktop(&config, "claude", NotificationText::plain(task_info), &None, "", true, None).await),
+        "hooks" => Ok(run_hook(&config, "claude", task_info, None, "", true, None)
+            .unwrap_or_else(|| json!({ "channel": "hooks", "ok": false, "error": "not configured" }))),
+        _ => Err(format!("未知通道：{}，可选 {}", channel, TEST_CHANNELS.join(" / "))),
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChannelTestResult {
+    pub channel: String,
+    pub ok: bool,
+    pub error: Option<String>,
+    pub latency_ms: u64,
+}
+
+// 用固定的示例消息把每个通道各测一次；被配置或来源开关挡住的通道不发送，只在结果里说明原因
+pub async fn test_all_channels(source: &str) -> Result<Vec<ChannelTestResult>, String> {
+    let config = load_config().map_err(|e| e.to_string())?;
+    let source_config = get_source_config(&config, source);
+    let task_info = "这是一条测试通知";
+    let gate = |channel: &str, unavailable: Option<&str>| -> Option<String> {
+        if let Some(reason) = unavailable {
+            Some(reason.to_string())
+        } else if !source_config.enabled {
+            Some("source disabled".to_string())
+        } else if !channel_routed(&config, source, Some("complete"), channel) {
+            Some(format!("{} disabled for this source", channel))
+        } else {
+            None
+        }
+    };
+    let outcome = |channel: &str, result: serde_json::Value, started: std::time::Instant| ChannelTestResult {
+        channel: channel.to_string(),
+        ok: result.get("ok").and_then(|v| v.as_bool()).unwrap_or(false),
+        error: result.get("error").and_then(|v| v.as_str()).map(str::to_string),
+        latency_ms: started.elapsed().as_millis() as u64,
+    };
+    let skipped = |channel: &str, error: String| ChannelTestResult {
+        channel: channel.to_string(),
+        ok: false,
+        error: Some(error),
+        latency_ms: 0,
+    };
+
+    let mut results = Vec::new();
+    results.push(match gate("desktop", (!config.channels.desktop.enabled).then_some("disabled")) {
+        Some(error) => skipped("desktop", error),
+        None => {
+            let started = std::time::Instant::now();
+            let result = send_desktop(&config, source, NotificationText::plain(task_info), &None, "", true, Some("complete")).await;
+            outcome("desktop", result, started)
+        }
+    });
+    let hook_missing = config.hooks.on_complete_command.trim().is_empty();
+    results.push(match gate("hooks", hook_missing.then_some("not configured")) {
+        Some(error) => skipped("hooks", error),
+        None => {
+            let started = std::time::Instant::now();
+            let result = run_hook(&config, source, task_info, None, "", true, Some("complete"))
+                .unwrap_or_else(|| json!({ "ok": false, "error": "not configured" }));
+            outcome("hooks", result, started)
+        }
+    });
+    Ok(results)
+}
+
+// 判断 now 是否落在 [start, end) 内；start > end 表示跨越午夜，start == end 视为空窗口
+fn in_quiet_window(now: NaiveTime, start: NaiveTime, end: NaiveTime) -> bool {
+    if start == end {
+        false
+    } else if start < end {
+        now >= start && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+fn is_quiet_hours_now(quiet_hours: &QuietHoursConfig) -> bool {
+    if !quiet_hours.enabled {
+        return false;
+    }
+    let parse = |value: &str| NaiveTime::parse_from_str(value.trim(), "%H:%M").ok();
+    match (parse(&quiet_hours.start), parse(&quiet_hours.end)) {
+        (Some(start), Some(end)) => in_quiet_window(Local::now().time(), start, end),
+        _ => false,
+    }
+}
+
+fn quiet_hours_suppresses(quiet_hours: &QuietHoursConfig, channel: &str) -> bool {
+    quiet_hours.channels.iter().any(|c| c.trim().eq_ignore_ascii_case(channel))
+}
+
+// routing 中有对应条目时按其决定（来源条目优先于 "*"）；
+// 否则回退到来源自身的通道开关，hooks 没有来源开关，配置了命令即执行
+fn channel_routed(config: &AppConfig, source: &str, notification_type: Option<&str>, channel: &str) -> bool {
+    let notification_type = notification_type.unwrap_or("complete");
+    let routed = [source, "*"]
+        .iter()
+        .find_map(|key| config.routing.get(*key).and_then(|types| types.get(notification_type)));
+    match routed {
+        Some(channels) => channels.iter().any(|c| c == channel),
+        None => channel != "desktop" || get_source_config(config, source).channels.desktop,
+    }
+}
+
+fn get_source_config<'a>(config: &'a AppConfig, source: &str) -> &'a SourceConfig {
+    config.sources.get(source).unwrap_or(&config.sources.claude)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationUrgency {
+    Low,
+    Normal,
+    Critical,
+}
+
+impl NotificationUrgency {
+    // 按通知类型读取 channels.urgency，各通道再映射为自身的优先级
+    pub fn for_notification(config: &AppConfig, notification_type: Option<&str>) -> Self {
+        let urgency = &config.channels.urgency;
+        let level = match notification_type {
+            Some("confirm") => &urgency.confirm,
+            Some("error") => &urgency.error,
+            _ => &urgency.complete,
+        };
+        match level.as_str() {
+            "low" => Self::Low,
+            "critical" => Self::Critical,
+            _ => Self::Normal,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Normal => "normal",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+// sources.<name>.min_duration_minutes 对所有通道生效；设置了阈值而耗时未知时同样视为不足
+fn below_min_duration(source_config: &SourceConfig, duration_ms: Option<i64>) -> bool {
+    let min_minutes = source_config.min_duration_minutes.max(0) as i64;
+    min_minutes > 0 && duration_ms.map(|ms| ms < min_minutes * 60_000).unwrap_or(true)
+}
+
+fn hook_command<'a>(config: &'a AppConfig, notification_type: Option<&str>) -> &'a str {
+    match notification_type {
+        Some("confirm") => config.hooks.on_confirm_command.trim(),
+        _ => config.hooks.on_complete_command.trim(),
+    }
+}
+
+// 执行用户配置的 hook 命令：后台运行，失败只记录日志，不影响通知结果。
+// 未配置或来源被禁用时返回 None。
+fn run_hook(
     config: &AppConfig,
     source: &str,
     task_info: &str,
+    duration_ms: Option<i64>,
+    cwd: &str,
+    force: bool,
+    notification_type: Option<&str>,
+) -> Option<serde_json::Value> {
+    let command = hook_command(config, notification_type);
+    if command.is_empty() {
+        return None;
+    }
+
+    let source_config = get_source_config(config, source);
+    if !force && (!source_config.enabled || !channel_routed(config, source, notification_type, "hooks")) {
+        return None;
+    }
+
+    if !force && below_min_duration(source_config, duration_ms) {
+        return Some(json!({ "channel": "hooks", "ok": false, "error": "below min duration" }));
+    }
+
+    let mut cmd = build_shell_command(command);
+    cmd.env("AITIFY_SOURCE", source)
+        .env("AITIFY_TASK_INFO", clamp_message(task_info, config.channels.max_message_chars))
+        .env("AITIFY_DURATION_MS", duration_ms.map(|ms| ms.to_string()).unwrap_or_default())
+        .env("AITIFY_CWD", cwd)
+        .env("AITIFY_URGENCY", NotificationUrgency::for_notification(config, notification_type).as_str())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    if !cwd.is_empty() && Path::new(cwd).is_dir() {
+        cmd.current_dir(cwd);
+    }
+
+    match cmd.spawn() {
+        Ok(mut child) => {
+            std::thread::spawn(move || {
+                let _ = child.wait();
+            });
+            Some(json!({ "channel": "hooks", "ok": true }))
+        }
+        Err(e) => {
+            eprintln!("Failed to run notification hook {:?}: {}", command, e);
+            Some(json!({ "channel": "hooks", "ok": false, "error": e.to_string() }))
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn build_shell_command(command: &str) -> Command {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command).creation_flags(CREATE_NO_WINDOW);
+    cmd
+}
+
+#[cfg(not(target_os = "windows"))]
+fn build_shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+async fn send_desktop(
+    config: &AppConfig,
+    source: &str,
+    text: NotificationText<'_>,
     duration_ms: &Option<i64>,
+    cwd: &str,
     force: bool,
     notification_type: Option<&str>,
 ) -> serde_json::Value {
-    let source_config = match source {
-        "claude" => &config.sources.claude,
-        "codex" => &config.sources.codex,
-        "gemini" => &config.sources.gemini,
-        "qwen" => &config.sources.qwen,
-        "opencode" => &config.sources.opencode,
-        _ => &config.sources.claude,
-    };
+    let source_config = get_source_config(config, source);
 
     if !force && !config.channels.desktop.enabled {
         return json!({ "channel": "desktop", "ok": false, "error": "disabled" });
     }
 
-    if !force && (!source_config.enabled || !source_config.channels.desktop) {
+    if !force && (!source_config.enabled || !channel_routed(config, source, notification_type, "desktop")) {
         return json!({ "channel": "desktop", "ok": false, "error": "source disabled" });
     }
 
-    if !force {
-        let min_minutes = source_config.min_duration_minutes.max(0) as i64;
-        if min_minutes > 0 {
-            let min_duration_ms = min_minutes * 60_000;
-            let below_minimum = duration_ms.map(|ms| ms < min_duration_ms).unwrap_or(true);
-            if below_minimum {
-                return json!({ "channel": "desktop", "ok": false, "error": "below min duration" });
-            }
-        }
+    if !force && below_min_duration(source_config, *duration_ms) {
+        return json!({ "channel": "desktop", "ok": false, "error": "below min duration" });
     }
 
+    let (title, content) = render_desktop_message(config, source, text, duration_ms, cwd, notification_type);
+    let urgency = NotificationUrgency::for_notification(config, notification_type);
+
     #[cfg(target_os = "windows")]
     {
         use winrt_notification::Toast;
 
-        let duration_text = duration_ms.map(|ms| {
-            let minutes = ms / 60000;
-            let seconds = (ms % 60000) / 1000;
-            if minutes > 0 {
-                format!("{} 分{}秒", minutes, seconds)
-            } else {
-                format!("{} 秒", seconds)
-            }
-        });
-
-        // 根据通知类型设置不同的标题
-        let title = match notification_type {
-            Some("confirm") => format!("{} 待确认", source.to_uppercase()),
-            Some("complete") | None => format!("{} 任务完成", source.to_uppercase()),
-            _ => format!("{} 任务完成", source.to_uppercase()),
-        };
-
-        let base_content = if task_info.trim().is_empty() {
-            match notification_type {
-                Some("confirm") => "需要你的确认".to_string(),
-                _ => "任务已完成".to_string(),
-            }
-        } else {
-            task_info.to_string()
-        };
-
-        let content = if let Some(dur) = duration_text {
-            format!("{} · 耗时 {}", base_content, dur)
-        } else {
-            base_content
-        };
-
         let mut errors = Vec::with_capacity(3);
 
         register_app_id(PRIMARY_APP_ID);
         register_app_id(LEGACY_APP_ID);
 
         for app_id in [PRIMARY_APP_ID, LEGACY_APP_ID, Toast::POWERSHELL_APP_ID] {
-            let toast = Toast::new(app_id).title(&title).text1(&content);
+            // 只有短（约 7 秒）/ 长（约 25 秒）两档，balloon_ms >= 9000 或紧急通知时取长档
+            let duration = if config.channels.desktop.balloon_ms >= 9000 || urgency == NotificationUrgency::Critical {
+                winrt_notification::Duration::Long
+            } else {
+                winrt_notification::Duration::Short
+            };
+            let mut toast = Toast::new(app_id).title(&title).text1(&content).duration(duration);
+            // 低优先级通知不播放提示音
+            if urgency == NotificationUrgency::Low {
+                toast = toast.sound(None);
+            }
             match toast.show() {
                 Ok(_) => {
                     return json!({
                         "channel": "desktop",
                         "ok": true,
+                        "mechanism": "winrt",
                         "app_id": app_id
                     })
                 }
@@ -141,23 +1017,428 @@ async fn send_desktop(
             }
         }
 
-        json!({
-            "channel": "desktop",
-            "ok": false,
-            "error": errors.join(" | ")
-        })
+        // 受限环境下三个 AppUserModelID 可能都无法弹出 toast，最后改用通知插件
+        match show_plugin_notification(&title, &content, urgency) {
+            Ok(()) => json!({ "channel": "desktop", "ok": true, "mechanism": "plugin" }),
+            Err(e) => {
+                errors.push(format!("plugin: {}", e));
+                json!({
+                    "channel": "desktop",
+                    "ok": false,
+                    "error": errors.join(" | ")
+                })
+            }
+        }
     }
 
     #[cfg(not(target_os = "windows"))]
     {
-        json!({ "channel": "desktop", "ok": false, "error": "not supported on this platform" })
+        match show_plugin_notification(&title, &content, urgency) {
+            Ok(()) => json!({ "channel": "desktop", "ok": true, "mechanism": "plugin" }),
+            Err(e) => json!({ "channel": "desktop", "ok": false, "error": e }),
+        }
     }
 }
 
+fn show_plugin_notification(title: &str, content: &str, urgency: NotificationUrgency) -> Result<(), String> {
+    use tauri_plugin_notification::NotificationExt;
+
+    let app = APP_HANDLE.get().ok_or("app handle not ready")?;
+    // 通知插件不支持 urgency 提示，低优先级通知只做静音处理
+    let mut builder = app.notification().builder().title(title).body(content);
+    if urgency == NotificationUrgency::Low {
+        builder = builder.silent();
+    }
+    builder.show().map_err(|e| e.to_string())
+}
+
+// 桌面通知的标题与正文：有模板时按模板渲染，否则使用内置文案
+fn render_desktop_message(
+    config: &AppConfig,
+    source: &str,
+    text: NotificationText,
+    duration_ms: &Option<i64>,
+    cwd: &str,
+    notification_type: Option<&str>,
+) -> (String, String) {
+    let source_config = get_source_config(config, source);
+    let max_chars = config.channels.max_message_chars.min(DESKTOP_MAX_MESSAGE_CHARS);
+    let strings = notify_strings(&config.ui);
+    let (title, content) =
+        build_desktop_message(strings, duration_style(&config.ui), source, text.task_info, duration_ms, notification_type, max_chars);
+    let values = TemplateValues {
+        source,
+        project: project_name(cwd),
+        duration: duration_ms.map(|ms| format_duration(ms, duration_style(&config.ui))),
+        summary: text.summary,
+        notification_type: notification_type.unwrap_or("complete"),
+    };
+    let title = match (source_config.title_template.trim(), &values.project) {
+        ("", Some(project)) if config.ui.title_include_project => format!("[{}] {}", project, title),
+        ("", _) => title,
+        (template, _) => render_template(template, &values),
+    };
+    let content = match source_config.body_template.trim() {
+        "" => content,
+        template => clamp_message(&render_template(template, &values), max_chars),
+    };
+    (title, content)
+}
+
+// 通知模板中可用的占位符取值；缺少的可选值替换为空字符串
+struct TemplateValues<'a> {
+    source: &'a str,
+    project: Option<String>,
+    duration: Option<String>,
+    summary: &'a str,
+    notification_type: &'a str,
+}
+
+fn render_template(template: &str, values: &TemplateValues) -> String {
+    template
+        .replace("{source}", values.source)
+        .replace("{project}", values.project.as_deref().unwrap_or_default())
+        .replace("{duration}", values.duration.as_deref().unwrap_or_default())
+        .replace("{summary}", values.summary)
+        .replace("{type}", values.notification_type)
+        .trim()
+        .to_string()
+}
+
+// cwd 的最后一级目录名，兼容 Windows 与 Unix 分隔符
+pub fn project_name(cwd: &str) -> Option<String> {
+    cwd.trim()
+        .split(['/', '\\'])
+        .rfind(|part| !part.is_empty())
+        .map(str::to_string)
+}
+
+fn build_desktop_message(
+    strings: &NotifyStrings,
+    duration_style: &str,
+    source: &str,
+    task_info: &str,
+    duration_ms: &Option<i64>,
+    notification_type: Option<&str>,
+    max_chars: usize,
+) -> (String, String) {
+    let duration_text = duration_ms.map(|ms| format_duration(ms, duration_style));
+
+    // 根据通知类型设置不同的标题
+    let title = match notification_type {
+        Some("confirm") => format!("{} {}", source.to_uppercase(), strings.confirm_title),
+        Some("error") => format!("{} {}", source.to_uppercase(), strings.error_title),
+        Some("reply") => format!("{} {}", source.to_uppercase(), strings.reply_suffix),
+        _ => format!("{} {}", source.to_uppercase(), strings.complete_title),
+    };
+
+    let base_content = if task_info.trim().is_empty() {
+        match notification_type {
+            Some("confirm") => strings.confirm_body.to_string(),
+            Some("error") => strings.error_body.to_string(),
+            _ => strings.complete_body.to_string(),
+        }
+    } else {
+        clamp_message(task_info, max_chars)
+    };
+
+    let content = if let Some(dur) = duration_text {
+        format!("{} · {} {}", base_content, strings.duration_label, dur)
+    } else {
+        base_content
+    };
+
+    (title, content)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_in_quiet_window_handles_same_day_and_overnight_ranges() {
+        let t = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+
+        assert!(in_quiet_window(t(13, 0), t(12, 0), t(14, 0)));
+        assert!(!in_quiet_window(t(14, 0), t(12, 0), t(14, 0)));
+        assert!(!in_quiet_window(t(11, 59), t(12, 0), t(14, 0)));
+
+        assert!(in_quiet_window(t(23, 30), t(23, 0), t(8, 0)));
+        assert!(in_quiet_window(t(2, 0), t(23, 0), t(8, 0)));
+        assert!(!in_quiet_window(t(8, 0), t(23, 0), t(8, 0)));
+        assert!(!in_quiet_window(t(12, 0), t(23, 0), t(8, 0)));
+
+        assert!(!in_quiet_window(t(9, 0), t(9, 0), t(9, 0)));
+    }
+
+    #[test]
+    fn test_below_min_duration_treats_unknown_duration_as_short() {
+        let mut source = SourceConfig::default();
+        assert!(!below_min_duration(&source, None));
+
+        source.min_duration_minutes = 2;
+        assert!(below_min_duration(&source, None));
+        assert!(below_min_duration(&source, Some(119_999)));
+        assert!(!below_min_duration(&source, Some(120_000)));
+    }
+
+    #[test]
+    fn test_notification_urgency_follows_type_and_config() {
+        let mut config = AppConfig::default();
+        assert_eq!(NotificationUrgency::for_notification(&config, Some("confirm")), NotificationUrgency::Critical);
+        assert_eq!(NotificationUrgency::for_notification(&config, Some("complete")), NotificationUrgency::Normal);
+        assert_eq!(NotificationUrgency::for_notification(&config, None), NotificationUrgency::Normal);
+
+        config.channels.urgency.complete = "low".to_string();
+        assert_eq!(NotificationUrgency::for_notification(&config, Some("complete")), NotificationUrgency::Low);
+    }
+
+    #[test]
+    fn test_sanitize_summary_strips_ansi_and_blank_lines() {
+        let text = "\n  \x1b[1;32mAll tests passed\x1b[0m\n\n\n  \n\nSee \x1b[31mlog\x1b[m\n\nDone  \n";
+        assert_eq!(sanitize_summary(text), "All tests passed\n\nSee log\n\nDone");
+    }
+
+    #[test]
+    fn test_render_desktop_message_fills_templates_with_raw_summary() {
+        let mut config = AppConfig::default();
+        config.sources.claude.title_template = "✅ {project}: {source} {type} in {duration}".to_string();
+        config.sources.claude.body_template = "{project}: {summary}".to_string();
+        // 与 send_completion_notification 一样，task_info 含标题与用量，summary 只有摘录
+        let text = NotificationText { task_info: "Claude 任务已完成（42k tokens）：修复了登录问题", summary: "修复了登录问题" };
+
+        let (title, content) = render_desktop_message(&config, "claude", text, &Some(65_000), "/work/aitify", Some("complete"));
+        assert_eq!(title, "✅ aitify: claude complete in 1 分5秒");
+        assert_eq!(content, "aitify: 修复了登录问题");
+
+        // 缺少的占位符替换为空字符串
+        let (title, content) = render_desktop_message(&config, "claude", text, &None, "", Some("complete"));
+        assert_eq!(title, "✅ : claude complete in");
+        assert_eq!(content, ": 修复了登录问题");
+
+        // 没有模板时正文沿用完整的 task_info
+        config.sources.claude.body_template = String::new();
+        let (_, content) = render_desktop_message(&config, "claude", text, &None, "", Some("complete"));
+        assert_eq!(content, "Claude 任务已完成（42k tokens）：修复了登录问题");
+    }
+
+    #[test]
+    fn test_snooze_status_expires() {
+        let snooze = Snooze { until: Mutex::new(Some(10_000)) };
+        assert_eq!(
+            snooze.status(4_000),
+            SnoozeStatus { active: true, until: Some(10_000), remaining_ms: 6_000 }
+        );
+        assert!(!snooze.status(10_000).active);
+        assert!(!Snooze::default().status(0).active);
+    }
+
+    #[test]
+    fn test_channel_routed_prefers_source_entry_then_wildcard() {
+        let mut config = AppConfig::default();
+        assert!(channel_routed(&config, "claude", Some("confirm"), "desktop"));
+        assert!(channel_routed(&config, "claude", Some("confirm"), "hooks"));
+
+        config.sources.codex.channels.desktop = false;
+        assert!(!channel_routed(&config, "codex", None, "desktop"));
+
+        config.routing.insert("*".to_string(), HashMap::from([("confirm".to_string(), vec!["hooks".to_string()])]));
+        config.routing.insert("codex".to_string(), HashMap::from([("complete".to_string(), vec!["desktop".to_string()])]));
+        assert!(!channel_routed(&config, "claude", Some("confirm"), "desktop"));
+        assert!(channel_routed(&config, "claude", Some("confirm"), "hooks"));
+        // 来源条目覆盖来源的通道开关；None 视为 complete
+        assert!(channel_routed(&config, "codex", None, "desktop"));
+        assert!(!channel_routed(&config, "codex", None, "hooks"));
+        // 没有条目的类型回退到来源开关
+        assert!(channel_routed(&config, "claude", Some("error"), "desktop"));
+    }
+
+    #[test]
+    fn test_cwd_filter_allows_by_allowlist_and_blocklist() {
+        let mut ui = UiConfig::default();
+        assert!(cwd_filter_allows(&ui, "/home/me/scratch/demo"));
+
+        ui.cwd_blocklist = vec!["scratch".to_string()];
+        assert!(!cwd_filter_allows(&ui, "/home/me/scratch/demo"));
+        assert!(cwd_filter_allows(&ui, "/home/me/work/app"));
+
+        ui.cwd_allowlist = vec!["D:/Work/*".to_string()];
+        assert!(cwd_filter_allows(&ui, "D:\\work\\app"));
+        assert!(!cwd_filter_allows(&ui, "/home/me/work/app"));
+        // 未知工作目录不过滤
+        assert!(cwd_filter_allows(&ui, ""));
+    }
+
+    #[test]
+    fn test_completion_digest_accumulates_per_source() {
+        let digest = CompletionDigest::default();
+        assert!(digest.add("claude", NotificationText::plain("Claude 任务已完成"), Some(60_000), "/a"));
+        assert!(!digest.add("claude", NotificationText::plain("Claude 任务已完成：第二条"), Some(30_000), "/b"));
+        assert!(!digest.add("claude", NotificationText::plain("Claude 任务已完成：第三条"), None, "/b"));
+        assert!(digest.add("codex", NotificationText::plain("Codex 任务已完成"), None, ""));
+
+        let bucket = digest.take("claude").unwrap();
+        assert_eq!((bucket.count, bucket.total_duration_ms), (3, Some(90_000)));
+        assert_eq!(bucket.task_info, "Claude 任务已完成");
+        assert!(digest.take("claude").is_none());
+        // 取出后重新开始新的窗口
+        assert!(digest.add("claude", NotificationText::plain("Claude 任务已完成"), None, ""));
+    }
+
+    #[test]
+    fn test_notify_cooldown_uses_separate_buckets() {
+        let cooldown = NotifyCooldown::default();
+
+        assert!(cooldown.try_acquire("complete", 1_000, 5_000));
+        assert!(!cooldown.try_acquire("complete", 3_000, 5_000));
+        assert!(cooldown.try_acquire("confirm", 3_000, 5_000));
+        assert!(cooldown.try_acquire("complete", 6_000, 5_000));
+        assert!(!cooldown.try_acquire("confirm", 7_999, 5_000));
+    }
+
+    #[test]
+    fn test_quiet_hours_reports_skipped_hooks() {
+        let now = Local::now().time();
+        let mut config = AppConfig::default();
+        config.ui.quiet_hours = QuietHoursConfig {
+            enabled: true,
+            start: (now - chrono::Duration::hours(1)).format("%H:%M").to_string(),
+            end: (now + chrono::Duration::hours(1)).format("%H:%M").to_string(),
+            channels: vec!["desktop".to_string(), "hooks".to_string()],
+        };
+
+        // 未配置 hook 命令时只有桌面通道的结果
+        let result = tauri::async_runtime::block_on(deliver_notifications(
+            &config, "claude", NotificationText::plain("Claude 任务已完成"), None, "", false, Some("complete"),
+        ))
+        .unwrap();
+        assert_eq!(result["results"].as_array().map(Vec::len), Some(1));
+
+        config.hooks.on_complete_command = "echo done".to_string();
+        let result = tauri::async_runtime::block_on(deliver_notifications(
+            &config, "claude", NotificationText::plain("Claude 任务已完成"), None, "", false, Some("complete"),
+        ))
+        .unwrap();
+        assert_eq!(result["results"][0]["error"], "quiet hours");
+        assert_eq!(result["results"][1], json!({ "channel": "hooks", "ok": false, "error": "quiet hours" }));
+    }
+
+    #[test]
+    fn test_metrics_result_counts_filtered_as_skipped() {
+        assert_eq!(metrics_result(&json!({ "channel": "desktop", "ok": true })), "ok");
+        assert_eq!(metrics_result(&json!({ "channel": "desktop", "ok": false, "error": "quiet hours" })), "skipped");
+        assert_eq!(metrics_result(&json!({ "channel": "desktop", "ok": false, "skipped": true, "reason": "user active" })), "skipped");
+        assert_eq!(metrics_result(&json!({ "channel": "desktop", "ok": false, "error": "toast failed" })), "error");
+    }
+
+    #[test]
+    fn test_should_focus_after_skips_filtered_and_skipped_desktop() {
+        assert!(should_focus_after(&json!({ "channel": "desktop", "ok": true })));
+        assert!(should_focus_after(&json!({ "channel": "desktop", "ok": false, "error": "disabled" })));
+        assert!(!should_focus_after(&json!({ "channel": "desktop", "ok": false, "error": "below min duration" })));
+        assert!(!should_focus_after(&json!({ "channel": "desktop", "ok": false, "skipped": true, "reason": "user active" })));
+        assert!(!should_focus_after(&json!({ "channel": "desktop", "ok": false, "skipped": true, "reason": "window focused" })));
+    }
+
+    #[test]
+    fn test_first_reply_does_not_throttle_completion() {
+        let cooldown = NotifyCooldown::default();
+        let dedupe = CompletionDedupe::default();
+        let ui = UiConfig { notify_cooldown_ms: 60_000, completion_dedupe_ms: 60_000, ..UiConfig::default() };
+
+        // both 模式：先发首次回复，同一轮的完成通知仍然发出
+        assert_eq!(throttle_reason(&cooldown, &dedupe, &ui, "claude", "好的", Some("reply"), 1_000), None);
+        assert_eq!(throttle_reason(&cooldown, &dedupe, &ui, "claude", "好的", Some("complete"), 2_000), None);
+        assert_eq!(throttle_reason(&cooldown, &dedupe, &ui, "claude", "好的", Some("reply"), 3_000), Some("cooldown"));
+        assert_eq!(throttle_reason(&cooldown, &dedupe, &ui, "claude", "好的", Some("complete"), 4_000), Some("duplicate"));
+    }
+
+    #[test]
+    fn test_completion_dedupe_matches_normalized_text_per_source() {
+        let dedupe = CompletionDedupe::default();
+
+        assert!(!dedupe.is_duplicate("claude", "Claude 任务已完成：Done.", 1_000, 3_000));
+        assert!(dedupe.is_duplicate("claude", "claude 任务已完成： done", 2_000, 3_000));
+        assert!(!dedupe.is_duplicate("codex", "Claude 任务已完成：Done.", 2_000, 3_000));
+        assert!(!dedupe.is_duplicate("claude", "Claude 任务已完成：Done.", 5_000, 3_000));
+        assert!(!dedupe.is_duplicate("claude", "Claude 任务已完成：其他", 5_500, 3_000));
+    }
+
+    #[test]
+    fn test_notification_history_caps_and_returns_newest_first() {
+        let history = NotificationHistory::default();
+        for i in 0..(MAX_NOTIFICATION_HISTORY + 5) {
+            history.push(NotificationRecord {
+                timestamp: i as i64,
+                source: "codex".to_string(),
+                notification_type: "complete".to_string(),
+                task_info: format!("task {}", i),
+                channels: Vec::new(),
+            });
+        }
+
+        let all = history.recent(usize::MAX);
+        assert_eq!(all.len(), MAX_NOTIFICATION_HISTORY);
+        assert_eq!(all.first().map(|r| r.timestamp), Some((MAX_NOTIFICATION_HISTORY + 4) as i64));
+        assert_eq!(all.last().map(|r| r.timestamp), Some(5));
+
+        let recent = history.recent(2);
+        assert_eq!(recent.iter().map(|r| r.timestamp).collect::<Vec<_>>(), vec![204, 203]);
+    }
+
+    #[test]
+    fn test_format_token_count_and_cost() {
+        assert_eq!(format_token_count(850), "850");
+        assert_eq!(format_token_count(42_315), "42k");
+        assert_eq!(format_token_count(1_250_000), "1.2M");
+        assert_eq!(format_cost(0.123), "≈ $0.12");
+        assert_eq!(format_cost(0.004), "< $0.01");
+    }
+
+    #[test]
+    fn test_truncate_summary_respects_char_boundaries() {
+        assert_eq!(truncate_summary("  \n ", 150), None);
+        assert_eq!(truncate_summary("\x1b[32m通过\x1b[0m", 150).as_deref(), Some("通过"));
+        assert_eq!(
+            truncate_summary("已修复\n  登录问题", 150).as_deref(),
+            Some("已修复 登录问题")
+        );
+
+        let long = "修".repeat(200);
+        let summary = truncate_summary(&long, 150).unwrap();
+        assert_eq!(summary.chars().count(), 150);
+        assert!(summary.ends_with('…'));
+    }
+
+    #[test]
+    fn test_clamp_message_prefers_newline_and_keeps_char_boundary() {
+        assert_eq!(clamp_message("短消息", 10), "短消息");
+        assert_eq!(clamp_message("任务已经全部完成了", 5), "任务已经…");
+        // 换行在后半段时在换行处截断，太靠前时按字符截断
+        assert_eq!(clamp_message("first line\nsecond line", 15), "first line…");
+        assert_eq!(clamp_message("a\nbcdefghijkl", 6), "a\nbcd…");
+    }
+
+    #[test]
+    fn test_project_name_handles_separators() {
+        assert_eq!(project_name("/home/me/myrepo").as_deref(), Some("myrepo"));
+        assert_eq!(project_name("D:\\Code\\Aitify\\").as_deref(), Some("Aitify"));
+        assert_eq!(project_name("  "), None);
+    }
+
+    #[test]
+    fn test_build_desktop_message_uses_notify_language() {
+        let mut ui = crate::config::UiConfig::default();
+        let (title, content) = build_desktop_message(notify_strings(&ui), duration_style(&ui), "codex", "", &Some(65_000), Some("complete"), 200);
+        assert_eq!(title, "CODEX 任务完成");
+        assert_eq!(content, "任务已完成 · 耗时 1 分5秒");
+
+        ui.notify_language = "en-US".to_string();
+        let (title, content) = build_desktop_message(notify_strings(&ui), duration_style(&ui), "codex", "", &Some(5_000), Some("confirm"), 200);
+        assert_eq!(title, "CODEX needs confirmation");
+        assert_eq!(content, "Waiting for your confirmation · took 5s");
+    }
+
     #[test]
     fn test_send_desktop_respects_global_desktop_channel_setting() {
         let mut config = AppConfig::default();
@@ -168,8 +1449,9 @@ mod tests {
         let result = tauri::async_runtime::block_on(send_desktop(
             &config,
             "codex",
-            "Codex 任务已完成",
+            NotificationText::plain("Codex 任务已完成"),
             &Some(60_000),
+            "",
             false,
             Some("complete"),
         ));
@@ -0,0 +1,160 @@
+// 把 CLI 所在的终端 / IDE 窗口切到前台。读取其他进程的工作目录需要解析 PEB，
+// 这里只在终端与编辑器进程的窗口中按标题是否包含项目名（cwd 的最后一级目录）匹配。
+// 找到并切换成功时返回 true，调用方据此决定是否改为聚焦主窗口
+
+// 可作为目标的进程可执行文件名（小写）；浏览器、记事本等窗口标题即使含项目名也不切换
+#[cfg(any(target_os = "windows", test))]
+const TERMINAL_PROCESSES: &[&str] = &[
+    "windowsterminal.exe",
+    "cmd.exe",
+    "powershell.exe",
+    "pwsh.exe",
+    "conhost.exe",
+    "wezterm-gui.exe",
+    "alacritty.exe",
+    "mintty.exe",
+    "tabby.exe",
+    "code.exe",
+    "cursor.exe",
+    "windsurf.exe",
+    "idea64.exe",
+    "pycharm64.exe",
+    "webstorm64.exe",
+];
+
+#[cfg(any(target_os = "windows", test))]
+fn title_matches_project(title: &str, project: &str) -> bool {
+    !project.is_empty() && title.to_lowercase().contains(&project.to_lowercase())
+}
+
+// candidates 为 (窗口标题, 进程可执行文件名)，按枚举顺序（Z 序）取第一个标题包含项目名的终端 / 编辑器窗口
+#[cfg(any(target_os = "windows", test))]
+fn pick_window(candidates: &[(String, String)], project: &str) -> Option<usize> {
+    candidates.iter().position(|(title, exe)| {
+        TERMINAL_PROCESSES.contains(&exe.to_lowercase().as_str()) && title_matches_project(title, project)
+    })
+}
+
+#[cfg(target_os = "windows")]
+pub fn focus_terminal(cwd: &str) -> bool {
+    use std::ffi::c_void;
+
+    type Hwnd = *mut c_void;
+    type EnumWindowsProc = unsafe extern "system" fn(Hwnd, isize) -> i32;
+
+    const SW_RESTORE: i32 = 9;
+    const PROCESS_QUERY_LIMITED_INFORMATION: u32 = 0x1000;
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn EnumWindows(callback: EnumWindowsProc, lparam: isize) -> i32;
+        fn IsWindowVisible(hwnd: Hwnd) -> i32;
+        fn IsIconic(hwnd: Hwnd) -> i32;
+        fn GetWindowTextW(hwnd: Hwnd, text: *mut u16, max_count: i32) -> i32;
+        fn GetWindowThreadProcessId(hwnd: Hwnd, process_id: *mut u32) -> u32;
+        fn ShowWindow(hwnd: Hwnd, cmd_show: i32) -> i32;
+        fn SetForegroundWindow(hwnd: Hwnd) -> i32;
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn OpenProcess(desired_access: u32, inherit_handle: i32, process_id: u32) -> *mut c_void;
+        fn QueryFullProcessImageNameW(process: *mut c_void, flags: u32, name: *mut u16, size: *mut u32) -> i32;
+        fn CloseHandle(handle: *mut c_void) -> i32;
+    }
+
+    // 进程可执行文件名，无权限访问时为空字符串
+    fn process_exe_name(hwnd: Hwnd) -> String {
+        let mut pid = 0u32;
+        // SAFETY: hwnd 来自 EnumWindows，pid 是本地变量
+        unsafe { GetWindowThreadProcessId(hwnd, &mut pid) };
+        // SAFETY: 只申请查询权限，失败时返回空句柄
+        let process = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid) };
+        if process.is_null() {
+            return String::new();
+        }
+        let mut buf = [0u16; 1024];
+        let mut size = buf.len() as u32;
+        // SAFETY: buf 与 size 描述同一块本地缓冲区，句柄用完即关闭
+        let ok = unsafe { QueryFullProcessImageNameW(process, 0, buf.as_mut_ptr(), &mut size) };
+        unsafe { CloseHandle(process) };
+        if ok == 0 {
+            return String::new();
+        }
+        let path = String::from_utf16_lossy(&buf[..size as usize]);
+        path.rsplit(['\\', '/']).next().unwrap_or_default().to_string()
+    }
+
+    unsafe extern "system" fn collect(hwnd: Hwnd, lparam: isize) -> i32 {
+        // SAFETY: lparam 是下方传入的 Vec 指针，EnumWindows 返回前一直有效
+        let windows = &mut *(lparam as *mut Vec<(Hwnd, String)>);
+        if IsWindowVisible(hwnd) != 0 {
+            let mut buf = [0u16; 512];
+            let len = GetWindowTextW(hwnd, buf.as_mut_ptr(), buf.len() as i32);
+            if len > 0 {
+                windows.push((hwnd, String::from_utf16_lossy(&buf[..len as usize])));
+            }
+        }
+        1
+    }
+
+    let Some(project) = crate::notify::project_name(cwd) else {
+        return false;
+    };
+    let mut windows: Vec<(Hwnd, String)> = Vec::new();
+    // SAFETY: 回调只在 EnumWindows 调用期间访问 windows
+    unsafe { EnumWindows(collect, &mut windows as *mut Vec<(Hwnd, String)> as isize) };
+
+    let own_pid = std::process::id();
+    let candidates: Vec<(Hwnd, String, String)> = windows
+        .into_iter()
+        .filter(|(hwnd, _)| {
+            let mut pid = 0u32;
+            // SAFETY: 同上，只读取窗口所属进程 ID
+            unsafe { GetWindowThreadProcessId(*hwnd, &mut pid) };
+            pid != own_pid
+        })
+        .map(|(hwnd, title)| {
+            let exe = process_exe_name(hwnd);
+            (hwnd, title, exe)
+        })
+        .collect();
+    let titles: Vec<(String, String)> = candidates.iter().map(|(_, title, exe)| (title.clone(), exe.clone())).collect();
+    let Some(index) = pick_window(&titles, &project) else {
+        return false;
+    };
+
+    let hwnd = candidates[index].0;
+    // SAFETY: hwnd 是刚枚举到的顶层窗口；窗口已关闭时调用只会失败
+    unsafe {
+        if IsIconic(hwnd) != 0 {
+            ShowWindow(hwnd, SW_RESTORE);
+        }
+        SetForegroundWindow(hwnd) != 0
+    }
+}
+
+// 其他平台暂不支持，由调用方聚焦主窗口
+#[cfg(not(target_os = "windows"))]
+pub fn focus_terminal(_cwd: &str) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_window_only_picks_terminal_with_project_title() {
+        let candidates = vec![
+            ("README.md - aitify - Notepad".to_string(), "notepad.exe".to_string()),
+            ("Other project".to_string(), "WindowsTerminal.exe".to_string()),
+            ("C:\\work\\Aitify".to_string(), "WindowsTerminal.exe".to_string()),
+        ];
+        assert_eq!(pick_window(&candidates, "aitify"), Some(2));
+        // 只有非终端窗口命中时回退到主窗口
+        assert_eq!(pick_window(&candidates[..2], "aitify"), None);
+        assert_eq!(pick_window(&candidates, "missing"), None);
+        assert_eq!(pick_window(&candidates, ""), None);
+    }
+}
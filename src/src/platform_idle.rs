@@ -0,0 +1,90 @@
+// 查询用户已有多久没有键盘 / 鼠标输入（秒）。系统接口不可用时返回 None，
+// 调用方按“用户不在”处理，宁可多发通知也不漏发
+
+#[cfg(target_os = "windows")]
+pub fn idle_seconds() -> Option<u64> {
+    #[repr(C)]
+    struct LastInputInfo {
+        cb_size: u32,
+        dw_time: u32,
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetLastInputInfo(plii: *mut LastInputInfo) -> i32;
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetTickCount() -> u32;
+    }
+
+    let mut info = LastInputInfo {
+        cb_size: std::mem::size_of::<LastInputInfo>() as u32,
+        dw_time: 0,
+    };
+    // SAFETY: info 是按 LASTINPUTINFO 布局声明的本地变量，cb_size 已正确设置
+    let ok = unsafe { GetLastInputInfo(&mut info) };
+    if ok == 0 {
+        return None;
+    }
+    // SAFETY: GetTickCount 没有参数，也不会失败
+    let now = unsafe { GetTickCount() };
+    // 两者都是开机以来的毫秒数，约 49.7 天回绕一次，用 wrapping_sub 处理
+    Some(u64::from(now.wrapping_sub(info.dw_time)) / 1000)
+}
+
+#[cfg(target_os = "macos")]
+pub fn idle_seconds() -> Option<u64> {
+    // kCGEventSourceStateCombinedSessionState / kCGAnyInputEventType
+    const COMBINED_SESSION_STATE: i32 = 0;
+    const ANY_INPUT_EVENT_TYPE: u32 = u32::MAX;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGEventSourceSecondsSinceLastEventType(state_id: i32, event_type: u32) -> f64;
+    }
+
+    // SAFETY: 纯查询函数，参数为系统定义的常量
+    let seconds = unsafe { CGEventSourceSecondsSinceLastEventType(COMBINED_SESSION_STATE, ANY_INPUT_EVENT_TYPE) };
+    (seconds.is_finite() && seconds >= 0.0).then_some(seconds as u64)
+}
+
+// X11 下借助 xprintidle（输出毫秒数）；Wayland 或未安装时返回 None
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub fn idle_seconds() -> Option<u64> {
+    let output = std::process::Command::new("xprintidle")
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    parse_idle_millis(&String::from_utf8_lossy(&output.stdout)).map(|ms| ms / 1000)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn parse_idle_millis(text: &str) -> Option<u64> {
+    text.trim().parse::<u64>().ok()
+}
+
+// 用户在 threshold_secs 内有过输入时返回 true；阈值为 0 或无法获取空闲时间时返回 false
+pub fn user_recently_active(threshold_secs: i32) -> bool {
+    threshold_secs > 0 && idle_seconds().is_some_and(|idle| idle < threshold_secs as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    fn test_parse_idle_millis() {
+        assert_eq!(parse_idle_millis("15234\n"), Some(15234));
+        assert_eq!(parse_idle_millis("unknown"), None);
+    }
+
+    #[test]
+    fn test_user_recently_active_disabled_at_zero() {
+        assert!(!user_recently_active(0));
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use crate::config::ModelPrice;
+
+// 内置价格表（美元 / 百万 token）：模型名前缀, 输入, 缓存命中输入, 输出。
+// 按最长前缀匹配，可通过 settings.json 的 pricing 覆盖或补充。
+const BUILTIN_PRICES: &[(&str, f64, f64, f64)] = &[
+    ("gpt-5", 1.25, 0.125, 10.0),
+    ("gpt-5-codex", 1.25, 0.125, 10.0),
+    ("gpt-5-mini", 0.25, 0.025, 2.0),
+    ("gpt-5-nano", 0.05, 0.005, 0.4),
+    ("gpt-4.1", 2.0, 0.5, 8.0),
+    ("gpt-4.1-mini", 0.4, 0.1, 1.6),
+    ("o3", 2.0, 0.5, 8.0),
+    ("o4-mini", 1.1, 0.275, 4.4),
+    ("claude-opus-4", 15.0, 1.5, 75.0),
+    ("claude-opus-4-5", 5.0, 0.5, 25.0),
+    ("claude-sonnet-4", 3.0, 0.3, 15.0),
+    ("claude-3-7-sonnet", 3.0, 0.3, 15.0),
+    ("claude-haiku-4", 1.0, 0.1, 5.0),
+    ("claude-3-5-haiku", 0.8, 0.08, 4.0),
+];
+
+// 一轮任务的 token 用量；input_tokens 不含缓存命中部分
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TokenUsage {
+    pub model: Option<String>,
+    pub input_tokens: i64,
+    pub cached_input_tokens: i64,
+    pub output_tokens: i64,
+}
+
+impl TokenUsage {
+    pub fn total_tokens(&self) -> i64 {
+        self.input_tokens + self.cached_input_tokens + self.output_tokens
+    }
+
+    pub fn add(&mut self, other: &TokenUsage) {
+        self.input_tokens += other.input_tokens;
+        self.cached_input_tokens += other.cached_input_tokens;
+        self.output_tokens += other.output_tokens;
+    }
+
+    pub fn subtract(&mut self, other: &TokenUsage) {
+        self.input_tokens = (self.input_tokens - other.input_tokens).max(0);
+        self.cached_input_tokens = (self.cached_input_tokens - other.cached_input_tokens).max(0);
+        self.output_tokens = (self.output_tokens - other.output_tokens).max(0);
+    }
+}
+
+fn find_price(model: &str, overrides: &HashMap<String, ModelPrice>) -> Option<ModelPrice> {
+    let model = model.trim().to_lowercase();
+    let longest_match = |prefix: &str| model.starts_with(&prefix.to_lowercase()).then_some(prefix.len());
+
+    let custom = overrides
+        .iter()
+        .filter_map(|(prefix, price)| longest_match(prefix).map(|len| (len, price.clone())))
+        .max_by_key(|(len, _)| *len);
+    let builtin = BUILTIN_PRICES
+        .iter()
+        .filter_map(|(prefix, input, cached_input, output)| {
+            longest_match(prefix).map(|len| {
+                (len, ModelPrice { input: *input, cached_input: Some(*cached_input), output: *output })
+            })
+        })
+        .max_by_key(|(len, _)| *len);
+
+    // 自定义价格在前缀长度相同时优先
+    match (custom, builtin) {
+        (Some(custom), Some(builtin)) if builtin.0 > custom.0 => Some(builtin.1),
+        (Some(custom), _) => Some(custom.1),
+        (None, builtin) => builtin.map(|(_, price)| price),
+    }
+}
+
+// 未知模型返回 None，调用方不展示费用
+pub fn estimate_cost(model: &str, usage: &TokenUsage, overrides: &HashMap<String, ModelPrice>) -> Option<f64> {
+    let price = find_price(model, overrides)?;
+    let cached_input = price.cached_input.unwrap_or(price.input);
+    let cost = usage.input_tokens as f64 * price.input
+        + usage.cached_input_tokens as f64 * cached_input
+        + usage.output_tokens as f64 * price.output;
+    Some(cost / 1_000_000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_cost_uses_longest_prefix_and_overrides() {
+        let usage = TokenUsage {
+            model: None,
+            input_tokens: 100_000,
+            cached_input_tokens: 0,
+            output_tokens: 10_000,
+        };
+        let no_overrides = HashMap::new();
+
+        let cost = estimate_cost("gpt-5-mini-2025-08-07", &usage, &no_overrides).unwrap();
+        assert!((cost - 0.045).abs() < 1e-9);
+        assert_eq!(estimate_cost("unknown-model", &usage, &no_overrides), None);
+
+        let overrides = HashMap::from([(
+            "gpt-5".to_string(),
+            ModelPrice { input: 1.0, cached_input: None, output: 1.0 },
+        )]);
+        // 内置的 gpt-5-mini 前缀更长，仍然优先
+        assert!((estimate_cost("gpt-5-mini", &usage, &overrides).unwrap() - 0.045).abs() < 1e-9);
+        assert!((estimate_cost("gpt-5", &usage, &overrides).unwrap() - 0.11).abs() < 1e-9);
+    }
+}
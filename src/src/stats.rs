@@ -0,0 +1,162 @@
+use chrono::{Days, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+use crate::config::get_data_dir;
+
+// 每次通知追加一行 JSON，读取时再按日期与来源聚合，写入保持廉价
+pub fn get_stats_path() -> PathBuf {
+    get_data_dir().join("stats.jsonl")
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StatEntry {
+    // YYYY-MM-DD，按字符串比较即可排序
+    date: String,
+    source: String,
+    #[serde(rename = "type")]
+    notification_type: String,
+    #[serde(default)]
+    duration_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct DailySourceStats {
+    pub date: String,
+    pub source: String,
+    pub complete: u32,
+    pub confirm: u32,
+    pub error: u32,
+    pub total_duration_ms: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct SourceTotals {
+    pub source: String,
+    pub count: u32,
+    pub total_duration_ms: i64,
+    // 仅统计带耗时的记录
+    pub average_duration_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StatsSummary {
+    pub days: Vec<DailySourceStats>,
+    pub sources: Vec<SourceTotals>,
+}
+
+pub fn record_stat(source: &str, notification_type: Option<&str>, duration_ms: Option<i64>) {
+    let entry = StatEntry {
+        date: format_date(Local::now().date_naive()),
+        source: source.to_string(),
+        notification_type: notification_type.unwrap_or("complete").to_string(),
+        duration_ms,
+    };
+    let Ok(line) = serde_json::to_string(&entry) else { return; };
+
+    let _ = fs::create_dir_all(get_data_dir());
+    let Ok(mut file) = OpenOptions::new().create(true).append(true).open(get_stats_path()) else { return; };
+    let _ = writeln!(file, "{}", line);
+}
+
+// 最近 days 天（含今天）的统计，days <= 0 时按 1 天处理
+pub fn get_stats(days: i32) -> StatsSummary {
+    let today = Local::now().date_naive();
+    let since = today
+        .checked_sub_days(Days::new(days.max(1) as u64 - 1))
+        .unwrap_or(today);
+
+    let Ok(file) = fs::File::open(get_stats_path()) else { return StatsSummary::default(); };
+    let entries = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<StatEntry>(&line).ok());
+    aggregate_stats(entries, &format_date(since))
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+fn aggregate_stats<I>(entries: I, since: &str) -> StatsSummary
+where
+    I: IntoIterator<Item = StatEntry>,
+{
+    let mut daily: BTreeMap<(String, String), DailySourceStats> = BTreeMap::new();
+    let mut totals: BTreeMap<String, (SourceTotals, u32)> = BTreeMap::new();
+
+    for entry in entries.into_iter().filter(|e| e.date.as_str() >= since) {
+        let day = daily
+            .entry((entry.date.clone(), entry.source.clone()))
+            .or_insert_with(|| DailySourceStats {
+                date: entry.date.clone(),
+                source: entry.source.clone(),
+                ..Default::default()
+            });
+        match entry.notification_type.as_str() {
+            "confirm" => day.confirm += 1,
+            "error" => day.error += 1,
+            _ => day.complete += 1,
+        }
+
+        let (total, timed) = totals.entry(entry.source.clone()).or_insert_with(|| {
+            (SourceTotals { source: entry.source.clone(), ..Default::default() }, 0)
+        });
+        total.count += 1;
+        if let Some(duration) = entry.duration_ms.filter(|d| *d >= 0) {
+            day.total_duration_ms += duration;
+            total.total_duration_ms += duration;
+            *timed += 1;
+        }
+    }
+
+    StatsSummary {
+        days: daily.into_values().collect(),
+        sources: totals
+            .into_values()
+            .map(|(mut total, timed)| {
+                total.average_duration_ms = (timed > 0).then(|| total.total_duration_ms / timed as i64);
+                total
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregate_stats_groups_by_day_and_source() {
+        let day = |d| format_date(NaiveDate::from_ymd_opt(2024, 6, d).unwrap());
+        let entry = |date: String, source: &str, kind: &str, duration_ms| StatEntry {
+            date,
+            source: source.to_string(),
+            notification_type: kind.to_string(),
+            duration_ms,
+        };
+
+        let summary = aggregate_stats(
+            vec![
+                entry(day(1), "codex", "complete", Some(60_000)),
+                entry(day(2), "codex", "complete", Some(30_000)),
+                entry(day(2), "codex", "confirm", None),
+                entry(day(2), "claude", "error", Some(10_000)),
+                entry(day(3), "codex", "complete", Some(90_000)),
+            ],
+            &day(2),
+        );
+
+        assert_eq!(summary.days.len(), 3);
+        let codex_day2 = summary.days.iter().find(|d| d.date == day(2) && d.source == "codex").unwrap();
+        assert_eq!((codex_day2.complete, codex_day2.confirm, codex_day2.total_duration_ms), (1, 1, 30_000));
+
+        let codex = summary.sources.iter().find(|s| s.source == "codex").unwrap();
+        assert_eq!(codex.count, 3);
+        assert_eq!(codex.total_duration_ms, 120_000);
+        assert_eq!(codex.average_duration_ms, Some(60_000));
+    }
+}
@@ -0,0 +1,95 @@
+use chrono::Local;
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::config::get_data_dir;
+
+// 超过该大小时把当前文件改名归档，再写新文件；已写入的行不会被改动
+const TURN_LOG_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+pub fn get_turn_log_path() -> PathBuf {
+    get_data_dir().join("turns.jsonl")
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TurnRecord {
+    pub source: String,
+    pub start_ts: Option<i64>,
+    pub end_ts: i64,
+    pub duration_ms: Option<i64>,
+    // 只有 Claude / Codex / Gemini 能判断，其余来源为 null
+    pub had_tool_use: Option<bool>,
+    pub was_confirm: bool,
+    pub was_error: bool,
+    pub cwd: String,
+    pub summary: Option<String>,
+}
+
+impl TurnRecord {
+    pub fn new(source: &str, duration_ms: Option<i64>, cwd: &str) -> Self {
+        let end_ts = Local::now().timestamp_millis();
+        Self {
+            source: source.to_string(),
+            start_ts: duration_ms.map(|ms| end_ts - ms),
+            end_ts,
+            duration_ms,
+            had_tool_use: None,
+            was_confirm: false,
+            was_error: false,
+            cwd: cwd.to_string(),
+            summary: None,
+        }
+    }
+}
+
+// 未开启 turn_log.enabled 时不写入
+pub fn record_turn(record: &TurnRecord) {
+    let enabled = crate::config::load_config().map(|c| c.turn_log.enabled).unwrap_or(false);
+    if !enabled {
+        return;
+    }
+    let Ok(line) = serde_json::to_string(record) else { return; };
+    let _ = fs::create_dir_all(get_data_dir());
+    append_line(&get_turn_log_path(), &line, TURN_LOG_MAX_BYTES);
+}
+
+fn append_line(path: &Path, line: &str, max_bytes: u64) {
+    if fs::metadata(path).is_ok_and(|meta| meta.len() >= max_bytes) {
+        let archived = path.with_file_name(format!("turns-{}.jsonl", Local::now().format("%Y%m%d-%H%M%S")));
+        let _ = fs::rename(path, archived);
+    }
+    let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) else { return; };
+    let _ = writeln!(file, "{}", line);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_line_rotates_when_file_is_full() {
+        let dir = std::env::temp_dir().join(format!("aitify-turn-log-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("temp dir should be created");
+        let path = dir.join("turns.jsonl");
+
+        append_line(&path, "{\"n\":1}", 16);
+        append_line(&path, "{\"n\":2}", 16);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"n\":1}\n{\"n\":2}\n");
+
+        // 已达上限，归档后写入新文件
+        append_line(&path, "{\"n\":3}", 16);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"n\":3}\n");
+        let archived: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| name.starts_with("turns-"))
+            .collect();
+        assert_eq!(archived.len(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,21 +1,27 @@
 use rusqlite::{params, Connection, OpenFlags};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use serde_json::value::RawValue;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 use tokio::time::interval;
 
+use crate::pricing::TokenUsage;
+
 include!("watch/shared.rs");
 include!("watch/claude.rs");
 include!("watch/codex.rs");
 include!("watch/gemini_qwen.rs");
 include!("watch/opencode.rs");
+include!("watch/cursor.rs");
+include!("watch/aider.rs");
 include!("watch/runtime.rs");
+include!("watch/replay.rs");
+include!("watch/tail.rs");
 include!("watch/tests.rs");
@@ -0,0 +1,151 @@
+// ============ Aider Watch ============
+
+// aider 在项目目录下追加写入 .aider.chat.history.md：
+// "#### " 开头的行是用户输入，"> " 开头的行是工具输出，其余非空行视为助手回复。
+// 文件中没有逐条时间戳，这里用读取到该行的时间近似。
+
+struct AiderState {
+    current_file: Option<PathBuf>,
+    processed_offset: u64,
+    last_user_at: Option<i64>,
+    last_assistant_at: Option<i64>,
+    last_notified_assistant_at: Option<i64>,
+    last_assistant_content: Option<String>,
+    pending_cancel: Option<Arc<AtomicBool>>,
+}
+
+impl AiderState {
+    fn new() -> Self {
+        Self {
+            current_file: None,
+            processed_offset: 0,
+            last_user_at: None,
+            last_assistant_at: None,
+            last_notified_assistant_at: None,
+            last_assistant_content: None,
+            pending_cancel: None,
+        }
+    }
+
+    fn cancel_pending(&mut self) {
+        if let Some(flag) = self.pending_cancel.take() {
+            flag.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn reset_turn(&mut self) {
+        self.cancel_pending();
+        self.last_user_at = None;
+        self.last_assistant_at = None;
+        self.last_assistant_content = None;
+    }
+}
+
+// 扫描根目录：sources.aider.log_dir > AIDER_WATCH_ROOT > 当前工作目录
+fn get_aider_watch_root(home: &Path, log_dir: Option<&str>) -> Option<PathBuf> {
+    if let Some(dir) = log_dir.map(str::trim).filter(|d| !d.is_empty()) {
+        return Some(expand_home_path(home, dir));
+    }
+
+    std::env::var("AIDER_WATCH_ROOT")
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::current_dir().ok())
+}
+
+fn is_aider_history_file(_full_path: &Path, name: &str) -> bool {
+    name == AIDER_HISTORY_FILE
+}
+
+// 与 read_jsonl_objects_from_offset 相同的增量语义：只处理以换行结尾的完整行
+fn read_text_lines_from_offset<F>(
+    path: &Path,
+    offset: u64,
+    mut visit: F,
+) -> std::io::Result<u64>
+where
+    F: FnMut(&str),
+{
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+
+    let mut reader = BufReader::new(file);
+    let mut buf = Vec::new();
+    let mut next_offset = offset;
+
+    loop {
+        buf.clear();
+        let bytes_read = reader.read_until(b'\n', &mut buf)?;
+        if bytes_read == 0 || buf.last() != Some(&b'\n') {
+            break;
+        }
+
+        visit(String::from_utf8_lossy(&buf).trim_end_matches(['\r', '\n']));
+        next_offset += bytes_read as u64;
+    }
+
+    Ok(next_offset)
+}
+
+fn process_aider_line(line: &str, now: i64, state: &mut AiderState) {
+    let trimmed = line.trim();
+
+    if trimmed.starts_with("# aider chat started") {
+        state.reset_turn();
+        return;
+    }
+
+    if trimmed == "####" || trimmed.starts_with("#### ") {
+        if state.last_assistant_at.is_some() {
+            // 上一轮已有回复，新的用户输入开启新一轮
+            state.reset_turn();
+        } else {
+            state.cancel_pending();
+        }
+        if state.last_user_at.is_none() {
+            state.last_user_at = Some(now);
+        }
+        return;
+    }
+
+    if trimmed.is_empty() || trimmed.starts_with('>') || state.last_user_at.is_none() {
+        return;
+    }
+
+    state.last_assistant_at = Some(now);
+    let content = match state.last_assistant_content.take() {
+        Some(previous) => format!("{}\n{}", previous, trimmed),
+        None => trimmed.to_string(),
+    };
+    state.last_assistant_content = Some(compact_state_text(&content));
+}
+
+fn schedule_aider_notification(state: &mut AiderState, quiet_ms: u64) {
+    let (Some(user_at), Some(assistant_at)) = (state.last_user_at, state.last_assistant_at) else {
+        return;
+    };
+    if state.last_notified_assistant_at == Some(assistant_at) {
+        return;
+    }
+
+    state.cancel_pending();
+    let cancel = Arc::new(AtomicBool::new(false));
+    state.pending_cancel = Some(cancel.clone());
+    state.last_notified_assistant_at = Some(assistant_at);
+
+    let cwd = state
+        .current_file
+        .as_deref()
+        .and_then(|path| path.parent())
+        .map(|dir| dir.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let summary = state.last_assistant_content.clone();
+    let duration_ms = if assistant_at >= user_at { Some(assistant_at - user_at) } else { None };
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(quiet_ms)).await;
+        if cancel.load(Ordering::Relaxed) { return; }
+        let _ = crate::notify::send_completion_notification("aider", "Aider", summary, None, duration_ms, cwd, None).await;
+    });
+}
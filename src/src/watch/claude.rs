@@ -1,7 +1,6 @@
 // ============ Claude Watch ============
 
 struct ClaudeState {
-    current_file: Option<PathBuf>,
     last_file_size: u64,
     last_user_at: Option<i64>,
     last_assistant_at: Option<i64>,
@@ -10,13 +9,24 @@ struct ClaudeState {
     confirm_notified_for_turn: bool,
     last_cwd: Option<String>,
     last_assistant_had_tool_use: bool,
+    // 本轮（自上一条真实的用户消息起）是否调用过工具
+    turn_had_tool_use: bool,
+    last_assistant_content: Option<String>,
+    last_error: Option<String>,
+    // 真实的用户消息之后还没有出现助手回复
+    awaiting_first_reply: bool,
+    // 本轮累计用量；同一条消息会被拆成多条记录写入，按 message.id 只计最新一次
+    turn_usage: TokenUsage,
+    last_message_usage: Option<(String, TokenUsage)>,
     pending_cancel: Option<Arc<AtomicBool>>,
+    // 子代理（isSidechain）记录使用独立的状态，不影响主会话的轮次跟踪
+    is_subagent: bool,
+    subagent: Option<Box<ClaudeState>>,
 }
 
 impl ClaudeState {
     fn new() -> Self {
         Self {
-            current_file: None,
             last_file_size: 0,
             last_user_at: None,
             last_assistant_at: None,
@@ -25,7 +35,15 @@ impl ClaudeState {
             confirm_notified_for_turn: false,
             last_cwd: None,
             last_assistant_had_tool_use: false,
+            turn_had_tool_use: false,
+            last_assistant_content: None,
+            last_error: None,
+            awaiting_first_reply: false,
+            turn_usage: TokenUsage::default(),
+            last_message_usage: None,
             pending_cancel: None,
+            is_subagent: false,
+            subagent: None,
         }
     }
 
@@ -33,6 +51,23 @@ impl ClaudeState {
         if let Some(flag) = self.pending_cancel.take() {
             flag.store(true, Ordering::Relaxed);
         }
+        if let Some(subagent) = self.subagent.as_mut() {
+            subagent.cancel_pending();
+        }
+    }
+
+    // 返回处理该记录的状态；子代理记录在未开启 notify_subagents 时返回 None
+    fn state_for_record(&mut self, obj: &Value, notify_subagents: bool) -> Option<&mut ClaudeState> {
+        if obj.get("isSidechain").and_then(|v| v.as_bool()) != Some(true) {
+            return Some(self);
+        }
+        if !notify_subagents {
+            return None;
+        }
+        let subagent = self.subagent.get_or_insert_with(|| {
+            Box::new(ClaudeState { is_subagent: true, ..ClaudeState::new() })
+        });
+        Some(subagent)
     }
 
     fn reset_for_new_file(&mut self) {
@@ -44,7 +79,57 @@ impl ClaudeState {
         self.notified_for_turn = false;
         self.confirm_notified_for_turn = false;
         self.last_assistant_had_tool_use = false;
+        self.turn_had_tool_use = false;
+        self.last_assistant_content = None;
+        self.last_error = None;
+        self.subagent = None;
+        self.reset_usage();
+    }
+
+    fn reset_usage(&mut self) {
+        self.turn_usage = TokenUsage::default();
+        self.last_message_usage = None;
     }
+
+    fn record_usage(&mut self, message_id: &str, usage: TokenUsage) {
+        if let Some((id, previous)) = self.last_message_usage.take() {
+            if id == message_id {
+                self.turn_usage.subtract(&previous);
+            }
+        }
+        self.turn_usage.add(&usage);
+        if usage.model.is_some() {
+            self.turn_usage.model = usage.model.clone();
+        }
+        self.last_message_usage = Some((message_id.to_string(), usage));
+    }
+
+    fn turn_token_usage(&self) -> Option<TokenUsage> {
+        Some(self.turn_usage.clone()).filter(|u| u.total_tokens() > 0)
+    }
+}
+
+// cache_creation 按普通输入计价，cache_read 按缓存命中计价
+fn extract_claude_usage(message: &Value) -> Option<TokenUsage> {
+    let usage = message.get("usage")?;
+    let field = |name: &str| usage.get(name).and_then(|v| v.as_i64()).unwrap_or(0).max(0);
+    Some(TokenUsage {
+        model: message.get("model").and_then(|v| v.as_str()).map(str::to_string),
+        input_tokens: field("input_tokens") + field("cache_creation_input_tokens"),
+        cached_input_tokens: field("cache_read_input_tokens"),
+        output_tokens: field("output_tokens"),
+    })
+}
+
+// 工具结果也以 user 记录写入，不代表用户开始了新的任务
+fn is_claude_tool_result_only(obj: &Value) -> bool {
+    obj.get("message")
+        .and_then(|m| m.get("content"))
+        .and_then(|c| c.as_array())
+        .is_some_and(|items| {
+            !items.is_empty()
+                && items.iter().all(|item| item.get("type").and_then(|t| t.as_str()) == Some("tool_result"))
+        })
 }
 
 fn has_tool_use_content(obj: &Value) -> bool {
@@ -60,12 +145,162 @@ fn has_tool_use_content(obj: &Value) -> bool {
     false
 }
 
+// 只取 text 类型的内容块，忽略 tool_use / thinking
+fn extract_claude_assistant_text(obj: &Value) -> String {
+    let content = match obj.get("message").and_then(|m| m.get("content")) {
+        Some(c) => c,
+        None => return String::new(),
+    };
+    if let Some(text) = content.as_str() {
+        return text.to_string();
+    }
+    content
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter(|item| item.get("type").and_then(|t| t.as_str()) == Some("text"))
+                .filter_map(|item| item.get("text").and_then(|t| t.as_str()))
+                .filter(|text| !text.trim().is_empty())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default()
+}
+
+// API 出错时 Claude 会写入 isApiErrorMessage 的 assistant 记录；工具失败体现为 is_error 的 tool_result
+fn extract_claude_error(obj: &Value) -> Option<String> {
+    let message = obj.get("message");
+
+    if obj.get("type").and_then(|t| t.as_str()) == Some("assistant") {
+        let is_api_error = obj.get("isApiErrorMessage").and_then(|v| v.as_bool()) == Some(true)
+            || obj.get("error").is_some_and(|e| !e.is_null());
+        if !is_api_error {
+            return None;
+        }
+        let text = message.map(extract_text_from_any).unwrap_or_default();
+        return Some(if text.trim().is_empty() { "API error".to_string() } else { text });
+    }
+
+    message
+        .and_then(|m| m.get("content"))
+        .and_then(|c| c.as_array())
+        .and_then(|items| {
+            items.iter().find(|item| {
+                item.get("type").and_then(|t| t.as_str()) == Some("tool_result")
+                    && item.get("is_error").and_then(|v| v.as_bool()) == Some(true)
+            })
+        })
+        .map(|item| item.get("content").map(extract_text_from_any).unwrap_or_default())
+}
+
+// 本轮以错误结束时发出错通知，否则发完成通知；子代理的标题为 "Claude 子代理 ..."
+async fn send_claude_turn_notification(
+    error: Option<String>,
+    summary: Option<String>,
+    usage: Option<TokenUsage>,
+    duration_ms: i64,
+    cwd: String,
+    subagent: bool,
+    turn_had_tool_use: bool,
+) {
+    let display_name = if subagent {
+        let config = crate::config::load_config().unwrap_or_default();
+        format!("Claude {}", crate::i18n::notify_strings(&config.ui).subagent_label)
+    } else {
+        "Claude".to_string()
+    };
+    match error {
+        Some(message) => {
+            let _ = crate::notify::send_error_notification("claude", &display_name, &message, Some(duration_ms), cwd).await;
+        }
+        None if completion_allowed_for_tool_use("claude", turn_had_tool_use)
+            && completion_allowed_for_content(summary.as_deref()) =>
+        {
+            let _ = crate::notify::send_completion_notification("claude", &display_name, summary, usage, Some(duration_ms), cwd, Some(turn_had_tool_use)).await;
+        }
+        None => {}
+    }
+}
+
+// 从 offset 起检查是否出现了晚于 assistant_at 的工作记录（工具调用、工具结果、进度等）；
+// 有则返回新的读取位置，供计时器重新计时
+fn claude_work_after(path: &Path, offset: u64, assistant_at: i64, subagent: bool) -> Option<u64> {
+    let mut found = false;
+    let next_offset = read_jsonl_objects_from_offset(path, offset, |obj| {
+        let is_sidechain = obj.get("isSidechain").and_then(|v| v.as_bool()) == Some(true);
+        if is_sidechain != subagent {
+            return;
+        }
+        if obj.get("timestamp").and_then(parse_timestamp).is_some_and(|ts| ts < assistant_at) {
+            return;
+        }
+        found |= match obj.get("type").and_then(|v| v.as_str()) {
+            Some("assistant") => has_tool_use_content(&obj),
+            Some("user") => is_claude_tool_result_only(&obj),
+            Some(work_type) => is_claude_work_type(work_type),
+            None => false,
+        };
+    })
+    .ok()?;
+    found.then_some(next_offset)
+}
+
+// 助手消息更新后重新安排完成通知：先取消旧的计时器，本轮已通知过确认时不再安排。
+// file 为正在跟随的会话文件，计时结束时用它复查新写入的记录。
+// 返回实际使用的去抖时长；没有安排时返回 None
+fn schedule_claude_notification(state: &mut ClaudeState, quiet_ms: u64, file: Option<&Path>) -> Option<u64> {
+    let (Some(user_at), Some(assistant_at)) = (state.last_user_at, state.last_assistant_at) else {
+        return None;
+    };
+    if assistant_at < user_at {
+        return None;
+    }
+
+    // Always cancel old timer first (mirrors JS: clearTimeout before rescheduling)
+    state.cancel_pending();
+    if state.confirm_notified_for_turn {
+        return None;
+    }
+
+    let adaptive_ms = if state.last_assistant_had_tool_use { quiet_ms } else { quiet_ms.min(15000) };
+    let cancel = Arc::new(AtomicBool::new(false));
+    state.pending_cancel = Some(cancel.clone());
+    let cwd = state.last_cwd.clone().unwrap_or_default();
+    let summary = state.last_assistant_content.clone();
+    let error = state.last_error.clone();
+    let usage = state.turn_token_usage();
+    let duration_ms = assistant_at - user_at;
+    let subagent = state.is_subagent;
+    let turn_had_tool_use = state.turn_had_tool_use;
+    let mut recheck = file.map(|path| (path.to_path_buf(), safe_stat(path).map(|s| s.len()).unwrap_or(0)));
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_millis(adaptive_ms)).await;
+            if cancel.load(Ordering::Relaxed) { return; }
+            // 计时期间又写入了工作记录但主循环还没读到，说明仍在工作，重新计时而不是通知
+            let Some((path, offset)) = recheck.as_mut() else { break; };
+            match claude_work_after(path, *offset, assistant_at, subagent) {
+                Some(next_offset) => *offset = next_offset,
+                None => break,
+            }
+        }
+        send_claude_turn_notification(error, summary, usage, duration_ms, cwd, subagent, turn_had_tool_use).await;
+    });
+    state.notified_for_turn = true;
+    state.confirm_notified_for_turn = true;
+    state.last_notified_at = Some(assistant_at);
+    Some(adaptive_ms)
+}
+
 fn process_claude_object(
     obj: &Value,
-    _seed: bool,
+    seed: bool,
     state: &mut ClaudeState,
 ) {
-    if obj.get("isSidechain").and_then(|v| v.as_bool()) == Some(true) {
+    // 主会话状态跳过子代理记录，子代理状态只处理子代理记录
+    let is_sidechain = obj.get("isSidechain").and_then(|v| v.as_bool()) == Some(true);
+    if is_sidechain != state.is_subagent {
         return;
     }
 
@@ -82,16 +317,42 @@ fn process_claude_object(
             state.confirm_notified_for_turn = false;
             state.notified_for_turn = false;
             state.last_assistant_had_tool_use = false;
+            state.last_assistant_content = None;
+            state.last_error = extract_claude_error(obj);
             state.last_user_at = ts;
+            if !is_claude_tool_result_only(obj) {
+                state.reset_usage();
+                state.turn_had_tool_use = false;
+                state.awaiting_first_reply = true;
+            }
         }
         Some("assistant") => {
             state.last_assistant_had_tool_use = has_tool_use_content(obj);
+            state.turn_had_tool_use |= state.last_assistant_had_tool_use;
+            // 助手继续回复说明之前的工具错误已被处理
+            state.last_error = extract_claude_error(obj);
+            if let Some(message) = obj.get("message") {
+                let message_id = message.get("id").and_then(|v| v.as_str()).unwrap_or_default();
+                if let Some(usage) = extract_claude_usage(message) {
+                    state.record_usage(message_id, usage);
+                }
+            }
+            let content = extract_claude_assistant_text(obj);
+            if !content.trim().is_empty() {
+                state.last_assistant_content = Some(compact_state_text(&content));
+            }
             state.last_assistant_at = ts.or_else(|| Some(now_unix_millis_i64()));
 
             if state.last_user_at.is_none() {
                 state.last_user_at = state.last_assistant_at;
                 state.notified_for_turn = false;
             }
+
+            if std::mem::take(&mut state.awaiting_first_reply) && !seed && !state.is_subagent {
+                let summary = Some(content).filter(|text| !text.trim().is_empty()).map(|text| compact_state_text(&text));
+                let cwd = state.last_cwd.clone().unwrap_or_default();
+                spawn_first_reply_notification("claude", "Claude", summary, state.last_user_at, state.last_assistant_at, cwd);
+            }
         }
         Some(work_type) if is_claude_work_type(work_type) => {
             // work in progress — cancel any pending completion timer
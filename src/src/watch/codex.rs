@@ -14,12 +14,25 @@ struct CodexSessionState {
     last_request_user_input_prompt: String,
     confirm_notified_for_turn: bool,
     interaction_required_for_turn: bool,
+    // 已为审批请求（执行命令 / 修改文件）发过确认提醒，批准后随工具输出清除
+    approval_requested_for_turn: bool,
     pending_request_user_input_call_ids: HashSet<String>,
     pending_request_user_input_without_id: usize,
     last_interaction_resolved_at: Option<i64>,
     collaboration_mode_kind: String,
-    // pending completion: (assistant_at, token_seen, cancel_flag)
-    pending_completion: Option<(i64, bool, Arc<AtomicBool>)>,
+    // 本轮是否出现过工具调用等工作事件
+    turn_had_work: bool,
+    // 新的轮次开始后还没有出现助手回复
+    awaiting_first_reply: bool,
+    // 待发的完成通知：(completion_at, cancel_flag)
+    pending_completion: Option<(i64, Arc<AtomicBool>)>,
+    // token_count 中的会话累计用量，以及本轮开始时的累计值
+    last_token_totals: Option<TokenUsage>,
+    turn_start_token_totals: Option<TokenUsage>,
+    model: Option<String>,
+    // 最近一次确认提醒，用于去重
+    last_confirm_key: Option<String>,
+    last_confirm_at: Option<i64>,
 }
 
 impl CodexSessionState {
@@ -38,29 +51,169 @@ impl CodexSessionState {
             last_request_user_input_prompt: String::new(),
             confirm_notified_for_turn: false,
             interaction_required_for_turn: false,
+            approval_requested_for_turn: false,
             pending_request_user_input_call_ids: HashSet::new(),
             pending_request_user_input_without_id: 0,
             last_interaction_resolved_at: None,
             collaboration_mode_kind: String::new(),
+            turn_had_work: false,
+            awaiting_first_reply: false,
             pending_completion: None,
+            last_token_totals: None,
+            turn_start_token_totals: None,
+            model: None,
+            last_confirm_key: None,
+            last_confirm_at: None,
         }
     }
 
     fn clear_pending_completion(&mut self) {
-        if let Some((_, _, cancel)) = self.pending_completion.take() {
+        if let Some((_, cancel)) = self.pending_completion.take() {
             cancel.store(true, Ordering::Relaxed);
         }
     }
 
+    // response_item 与 event_msg 会各写一次同一条回复，只有本轮第一条触发通知
+    fn notify_first_reply(&mut self, text: Option<String>) {
+        if !std::mem::take(&mut self.awaiting_first_reply) || self.is_subagent_session {
+            return;
+        }
+        let summary = text.filter(|t| !t.trim().is_empty()).map(|t| compact_state_text(&t));
+        let cwd = self.last_cwd.clone().unwrap_or_default();
+        spawn_first_reply_notification("codex", "Codex", summary, self.last_user_at, self.last_assistant_at, cwd);
+    }
+
     fn reset_for_new_turn(&mut self) {
         self.clear_pending_completion();
         self.last_agent_content = None;
         self.confirm_notified_for_turn = false;
         self.interaction_required_for_turn = false;
+        self.approval_requested_for_turn = false;
         self.pending_request_user_input_call_ids.clear();
         self.pending_request_user_input_without_id = 0;
         self.last_interaction_resolved_at = None;
         self.last_request_user_input_prompt = String::new();
+        self.turn_had_work = false;
+        self.awaiting_first_reply = true;
+        self.turn_start_token_totals = self.last_token_totals.clone();
+    }
+
+    // 本轮用量；缺少本轮起点或累计值回退（新会话）时使用会话累计值
+    fn turn_token_usage(&self) -> Option<TokenUsage> {
+        let total = self.last_token_totals.as_ref()?;
+        let mut usage = total.clone();
+        if let Some(start) = self.turn_start_token_totals.as_ref() {
+            if total.total_tokens() >= start.total_tokens() {
+                usage.subtract(start);
+            }
+        }
+        usage.model = self.model.clone();
+        Some(usage).filter(|u| u.total_tokens() > 0)
+    }
+
+    fn should_send_confirm(&mut self, prompt: &str, at: i64) -> bool {
+        should_send_confirm(&mut self.last_confirm_key, &mut self.last_confirm_at, prompt, at)
+    }
+
+    // 安排完成通知；已有待发通知时先取消，按当前的总结与用量重新安排
+    fn schedule_completion(&mut self, completion_at: i64, delay_ms: u64) {
+        self.clear_pending_completion();
+        let start_at = self.last_user_at.or(self.last_task_started_at);
+        let duration_ms = start_at.map(|start| if completion_at >= start { completion_at - start } else { 0 });
+        let cwd = self.last_cwd.clone().unwrap_or_default();
+        let summary = self.last_agent_content.clone();
+        let usage = self.turn_token_usage();
+        let turn_had_work = self.turn_had_work;
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.pending_completion = Some((completion_at, cancel.clone()));
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+            if cancel.load(Ordering::Relaxed) { return; }
+            if !completion_allowed_for_tool_use("codex", turn_had_work) { return; }
+            if !completion_allowed_for_content(summary.as_deref()) { return; }
+            let _ = crate::notify::send_completion_notification("codex", "Codex", summary, usage, duration_ms, cwd, Some(turn_had_work)).await;
+        });
+    }
+}
+
+// 会话文件首行是 session_meta（旧版为带 id / timestamp 的头部对象），
+// 其余行也都带有 Codex 的记录类型；用于排除目录里混入的其他 .jsonl
+fn is_codex_session_header(line: &str) -> bool {
+    let Ok(Value::Object(obj)) = serde_json::from_str::<Value>(line.trim()) else {
+        return false;
+    };
+    match obj.get("type").and_then(|v| v.as_str()) {
+        Some(kind) => matches!(kind, "session_meta" | "turn_context" | "response_item" | "event_msg"),
+        None => obj.contains_key("id") && obj.contains_key("timestamp"),
+    }
+}
+
+// 首行已写完时返回是否为 Codex 会话头；文件刚创建、首行还没写完时返回 None
+fn read_codex_session_header(path: &Path) -> Option<bool> {
+    const MAX_HEADER_BYTES: u64 = 1024 * 1024;
+    let file = File::open(path).ok()?;
+    let mut line = Vec::new();
+    let read = BufReader::new(file).take(MAX_HEADER_BYTES).read_until(b'\n', &mut line).ok()?;
+    if line.last() != Some(&b'\n') {
+        // 超过上限仍没有换行的不是会话文件
+        return (read as u64 >= MAX_HEADER_BYTES).then_some(false);
+    }
+    Some(is_codex_session_header(&String::from_utf8_lossy(&line)))
+}
+
+// 按路径缓存首行检查结果；会话文件只追加，首行写完后不会再变
+#[derive(Default)]
+struct CodexHeaderCache {
+    checked: HashMap<PathBuf, bool>,
+}
+
+impl CodexHeaderCache {
+    fn is_session_file(&mut self, path: &Path) -> bool {
+        if let Some(valid) = self.checked.get(path) {
+            return *valid;
+        }
+        match read_codex_session_header(path) {
+            Some(valid) => {
+                self.checked.insert(path.to_path_buf(), valid);
+                valid
+            }
+            None => false,
+        }
+    }
+}
+
+// task_complete 之后常紧跟 token_count 与最后一条 agent_message；
+// quiet_ms 为 0 时也至少等待一个宽限期，让通知带上最终内容
+fn codex_completion_delay_ms(quiet_ms: u64) -> u64 {
+    if quiet_ms > 0 { quiet_ms } else { get_codex_token_grace_ms() }
+}
+
+// 兼容 { info: { total_token_usage: {...} } } 与扁平字段两种结构；
+// Codex 的 input_tokens 包含缓存命中部分，这里拆开以便分别计价
+fn extract_codex_token_totals(payload: &serde_json::Map<String, Value>) -> Option<TokenUsage> {
+    let usage = payload
+        .get("info")
+        .and_then(|info| info.get("total_token_usage"))
+        .or_else(|| payload.get("total_token_usage"))
+        .or_else(|| payload.get("info"));
+    let field = |name: &str| {
+        usage
+            .and_then(|u| u.get(name))
+            .or_else(|| payload.get(name))
+            .and_then(|v| v.as_i64())
+    };
+
+    match (field("input_tokens"), field("output_tokens")) {
+        (None, None) => field("total_tokens").map(|total| TokenUsage { input_tokens: total, ..Default::default() }),
+        (input, output) => {
+            let cached = field("cached_input_tokens").unwrap_or(0).max(0);
+            Some(TokenUsage {
+                model: None,
+                input_tokens: (input.unwrap_or(0) - cached).max(0),
+                cached_input_tokens: cached,
+                output_tokens: output.unwrap_or(0),
+            })
+        }
     }
 }
 
@@ -145,10 +298,36 @@ fn extract_request_user_input_text(payload: &Value) -> String {
     }
 }
 
+// 审批请求的提示文案：附上请求执行的命令或要修改的文件
+fn codex_approval_prompt(approval_type: &str, payload: &serde_json::Map<String, Value>) -> String {
+    let detail = if approval_type == "apply_patch_approval_request" {
+        payload
+            .get("changes")
+            .and_then(|v| v.as_object())
+            .map(|changes| changes.keys().cloned().collect::<Vec<_>>().join("、"))
+            .unwrap_or_default()
+    } else {
+        match payload.get("command") {
+            Some(Value::Array(parts)) => parts.iter().filter_map(|p| p.as_str()).collect::<Vec<_>>().join(" "),
+            Some(Value::String(command)) => command.clone(),
+            _ => String::new(),
+        }
+    };
+    let action = if approval_type == "apply_patch_approval_request" { "请求修改文件" } else { "请求执行命令" };
+    let detail = compact_state_text(&detail);
+    if detail.is_empty() {
+        action.to_string()
+    } else {
+        format!("{}：{}", action, detail)
+    }
+}
+
 fn process_codex_object(
     obj: &Value,
     seed: bool,
     state: &mut CodexSessionState,
+    confirm: &ConfirmDetector,
+    quiet_ms: u64,
 ) {
     let ts = obj.get("timestamp").and_then(parse_timestamp);
 
@@ -182,6 +361,9 @@ fn process_codex_object(
                 }
                 state.current_turn_id = Some(next_turn_id);
             }
+            if let Some(model) = payload.get("model").and_then(|v| v.as_str()).filter(|m| !m.trim().is_empty()) {
+                state.model = Some(model.to_string());
+            }
             if let Some(collab_mode) = payload.get("collaboration_mode").and_then(|v| v.as_object()) {
                 if let Some(mode) = collab_mode.get("mode").and_then(|v| v.as_str()) {
                     state.collaboration_mode_kind = mode.to_string();
@@ -252,6 +434,7 @@ fn process_codex_object(
                     && state.pending_request_user_input_without_id == 0
                 {
                     state.interaction_required_for_turn = false;
+                    state.approval_requested_for_turn = false;
                     state.last_interaction_resolved_at = ts.or_else(|| Some(now_unix_millis_i64()));
                     state.last_request_user_input_prompt = String::new();
                 }
@@ -261,18 +444,23 @@ fn process_codex_object(
             // work type — cancel pending
             if payload_type.map(is_codex_work_type).unwrap_or(false) {
                 state.clear_pending_completion();
+                state.turn_had_work = true;
                 return;
             }
 
             // assistant message
             if payload_type == Some("message") && payload_role == Some("assistant") {
                 if seed { return; }
-                state.clear_pending_completion();
                 let assistant_text = extract_text_from_any(&Value::Object(payload.clone()));
                 if !assistant_text.is_empty() {
                     state.last_agent_content = Some(compact_state_text(&assistant_text));
                 }
                 state.last_assistant_at = ts.or_else(|| Some(now_unix_millis_i64()));
+                state.notify_first_reply(Some(assistant_text));
+                // 紧随 task_complete 的最终回复不取消通知，而是带上新内容重新安排
+                if let Some((completion_at, _)) = state.pending_completion {
+                    state.schedule_completion(completion_at, codex_completion_delay_ms(quiet_ms));
+                }
                 return;
             }
         }
@@ -333,19 +521,28 @@ fn process_codex_object(
                         .unwrap_or(false)
                         && payload.get("last_agent_message").and_then(|v| v.as_str()).map(|s| s.is_empty()).unwrap_or(true);
 
-                    if state.interaction_required_for_turn {
+                    // 过短的轮次不发确认提醒，直接走下面的完成通知
+                    let confirm_allowed = confirm.allows_turn(state.last_user_at.or(state.last_task_started_at), completion_at);
+
+                    // 审批请求当时已经提醒过，本轮结束时不再重复
+                    if state.interaction_required_for_turn && state.approval_requested_for_turn {
+                        if let Some(tid) = turn_id {
+                            state.last_notified_turn_id = Some(tid);
+                        }
+                        return;
+                    }
+
+                    if state.interaction_required_for_turn && confirm_allowed {
                         let request_prompt = state.last_request_user_input_prompt.clone();
                         let request_has_options = has_options_in_prompt(&request_prompt);
                         let agent_content = state.last_agent_content.clone().unwrap_or_default();
 
-                        if request_has_options {
-                            let cwd = state.last_cwd.clone().unwrap_or_default();
-                            tauri::async_runtime::spawn(async move {
-                                let _ = crate::notify::send_notifications("codex", &request_prompt, None, cwd, false, Some("confirm")).await;
-                            });
+                        let msg = if request_has_options {
+                            request_prompt
                         } else {
-                            let prompt = detect_turn_end_confirm_prompt(&agent_content);
-                            let msg = prompt.unwrap_or_else(|| "需要你的确认".to_string());
+                            confirm.detect(&agent_content).unwrap_or_else(|| "需要你的确认".to_string())
+                        };
+                        if state.should_send_confirm(&msg, completion_at) {
                             let cwd = state.last_cwd.clone().unwrap_or_default();
                             tauri::async_runtime::spawn(async move {
                                 let _ = crate::notify::send_notifications("codex", &msg, None, cwd, false, Some("confirm")).await;
@@ -361,14 +558,17 @@ fn process_codex_object(
                         return;
                     }
 
-                    if !assistant_stale {
+                    if !assistant_stale && confirm_allowed {
                         let agent_content = state.last_agent_content.clone().unwrap_or_default();
-                        let prompt = detect_turn_end_confirm_prompt(&agent_content);
+                        let prompt = confirm.detect(&agent_content);
                         if let Some(p) = prompt {
-                            let cwd = state.last_cwd.clone().unwrap_or_default();
-                            tauri::async_runtime::spawn(async move {
-                                let _ = crate::notify::send_notifications("codex", &p, None, cwd, false, Some("confirm")).await;
-                            });
+                            // 重复的确认提示不再提醒，但本轮仍按确认处理，不再发完成通知
+                            if state.should_send_confirm(&p, completion_at) {
+                                let cwd = state.last_cwd.clone().unwrap_or_default();
+                                tauri::async_runtime::spawn(async move {
+                                    let _ = crate::notify::send_notifications("codex", &p, None, cwd, false, Some("confirm")).await;
+                                });
+                            }
                             if let Some(tid) = turn_id {
                                 state.last_notified_turn_id = Some(tid);
                             }
@@ -376,21 +576,53 @@ fn process_codex_object(
                         }
                     }
 
-                    let start_at = state.last_user_at.or(state.last_task_started_at);
-                    let duration_ms = start_at.map(|start| {
-                        if completion_at >= start { completion_at - start } else { 0 }
-                    });
+                    // 去抖：新的用户消息或工作事件到来时取消，随后的 token_count / agent_message 会重新安排
+                    state.schedule_completion(completion_at, codex_completion_delay_ms(quiet_ms));
+
+                    state.last_notified_assistant_at = Some(completion_at);
+                    state.last_notified_turn_id = turn_id;
+                    state.confirm_notified_for_turn = true;
+                }
+
+                // stream_error 是可自动重试的断流提示，最终失败时 Codex 会再发 error，这里不单独通知
+                Some("error") => {
+                    if seed || state.is_subagent_session || state.confirm_notified_for_turn {
+                        return;
+                    }
 
+                    state.clear_pending_completion();
+                    let message = payload.get("message").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+                    let error_at = ts.unwrap_or_else(now_unix_millis_i64);
+                    let start_at = state.last_user_at.or(state.last_task_started_at);
+                    let duration_ms = start_at.map(|start| if error_at >= start { error_at - start } else { 0 });
                     let cwd = state.last_cwd.clone().unwrap_or_default();
                     tauri::async_runtime::spawn(async move {
-                        let _ = crate::notify::send_notifications("codex", "Codex 任务已完成", duration_ms, cwd, false, Some("complete")).await;
+                        let _ = crate::notify::send_error_notification("codex", "Codex", &message, duration_ms, cwd).await;
                     });
 
-                    state.last_notified_assistant_at = Some(completion_at);
-                    state.last_notified_turn_id = turn_id;
+                    // 同一轮随后的 task_complete 不再重复通知
                     state.confirm_notified_for_turn = true;
                 }
 
+                // 审批请求不依赖文本关键词，立即提醒
+                Some(approval_type @ ("exec_approval_request" | "apply_patch_approval_request")) => {
+                    state.clear_pending_completion();
+                    state.turn_had_work = true;
+                    state.interaction_required_for_turn = true;
+                    state.approval_requested_for_turn = true;
+                    let prompt = codex_approval_prompt(approval_type, payload);
+                    state.last_request_user_input_prompt = prompt.clone();
+                    if seed || state.is_subagent_session {
+                        return;
+                    }
+                    if state.should_send_confirm(&prompt, ts.unwrap_or_else(now_unix_millis_i64)) {
+                        let cwd = state.last_cwd.clone().unwrap_or_default();
+                        tauri::async_runtime::spawn(async move {
+                            let _ = crate::notify::send_notifications("codex", &prompt, None, cwd, false, Some("confirm")).await;
+                        });
+                    }
+                }
+
                 Some("user_message") => {
                     state.clear_pending_completion();
                     state.last_task_started_at = None;
@@ -399,22 +631,13 @@ fn process_codex_object(
                 }
 
                 Some("token_count") => {
+                    if let Some(totals) = extract_codex_token_totals(payload) {
+                        state.last_token_totals = Some(totals);
+                    }
+                    // 待发的完成通知按最新用量重新安排
                     if !seed {
-                        // mark token seen for pending completion grace period
-                        if let Some((asst_at, ref mut token_seen, ref cancel)) = state.pending_completion {
-                            if !*token_seen && !cancel.load(Ordering::Relaxed) {
-                                *token_seen = true;
-                                let cancel2 = cancel.clone();
-                                let grace_ms = get_codex_token_grace_ms();
-                                let cwd = state.last_cwd.clone().unwrap_or_default();
-                                let start_at = state.last_user_at.or(state.last_task_started_at);
-                                let duration_ms = start_at.map(|s| if asst_at >= s { asst_at - s } else { 0 });
-                                tauri::async_runtime::spawn(async move {
-                                    tokio::time::sleep(Duration::from_millis(grace_ms)).await;
-                                    if cancel2.load(Ordering::Relaxed) { return; }
-                                    let _ = crate::notify::send_notifications("codex", "Codex 任务已完成", duration_ms, cwd, false, Some("complete")).await;
-                                });
-                            }
+                        if let Some((completion_at, _)) = state.pending_completion {
+                            state.schedule_completion(completion_at, codex_completion_delay_ms(quiet_ms));
                         }
                     }
                 }
@@ -438,11 +661,17 @@ fn process_codex_object(
                         .or_else(|| payload_val.get("data").and_then(|d| d.as_str()))
                         .map(|s| s.to_string());
 
-                    if let Some(c) = content {
+                    if let Some(c) = content.as_ref() {
                         if !c.trim().is_empty() {
-                            state.last_agent_content = Some(compact_state_text(&c));
+                            state.last_agent_content = Some(compact_state_text(c));
                         }
                     }
+                    state.notify_first_reply(content);
+
+                    // task_complete 之后补写的最后一条消息作为通知总结
+                    if let Some((completion_at, _)) = state.pending_completion {
+                        state.schedule_completion(completion_at, codex_completion_delay_ms(quiet_ms));
+                    }
                 }
 
                 _ => {}
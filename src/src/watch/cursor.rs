@@ -0,0 +1,112 @@
+// ============ Cursor Watch ============
+
+// Cursor agent 的会话记录格式并不稳定，这里保守处理：
+// 只识别 user / assistant 两类记录（顶层 role/type、message.role 或 response_item 的 payload.role），
+// 助手消息停止更新超过去抖窗口后发送完成通知。
+
+struct CursorSessionState {
+    processed_offset: u64,
+    last_user_at: Option<i64>,
+    last_assistant_at: Option<i64>,
+    last_notified_assistant_at: Option<i64>,
+    last_assistant_content: Option<String>,
+    last_cwd: Option<String>,
+    pending_cancel: Option<Arc<AtomicBool>>,
+}
+
+impl CursorSessionState {
+    fn new() -> Self {
+        Self {
+            processed_offset: 0,
+            last_user_at: None,
+            last_assistant_at: None,
+            last_notified_assistant_at: None,
+            last_assistant_content: None,
+            last_cwd: None,
+            pending_cancel: None,
+        }
+    }
+
+    fn cancel_pending(&mut self) {
+        if let Some(flag) = self.pending_cancel.take() {
+            flag.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+fn cursor_record_role(obj: &Value) -> Option<&str> {
+    let payload = obj.get("payload");
+    let candidates = [
+        obj.get("role"),
+        obj.get("message").and_then(|m| m.get("role")),
+        payload.and_then(|p| p.get("role")),
+        obj.get("type"),
+    ];
+
+    candidates
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str())
+        .find(|role| matches!(*role, "user" | "assistant"))
+}
+
+fn process_cursor_object(obj: &Value, state: &mut CursorSessionState) {
+    let ts = obj
+        .get("timestamp")
+        .and_then(parse_timestamp)
+        .or_else(|| Some(now_unix_millis_i64()));
+
+    let cwd = obj
+        .get("cwd")
+        .or_else(|| obj.get("payload").and_then(|p| p.get("cwd")))
+        .and_then(|v| v.as_str());
+    if let Some(cwd) = cwd.filter(|c| !c.trim().is_empty()) {
+        state.last_cwd = Some(cwd.to_string());
+    }
+
+    match cursor_record_role(obj) {
+        Some("user") => {
+            state.cancel_pending();
+            state.last_user_at = ts;
+            state.last_assistant_at = None;
+            state.last_assistant_content = None;
+        }
+        Some("assistant") => {
+            state.last_assistant_at = ts;
+            let content = obj
+                .get("message")
+                .or_else(|| obj.get("payload").and_then(|p| p.get("content")))
+                .or_else(|| obj.get("content"))
+                .map(extract_text_from_any)
+                .unwrap_or_default();
+            if !content.trim().is_empty() {
+                state.last_assistant_content = Some(compact_state_text(&content));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn schedule_cursor_notification(state: &mut CursorSessionState, quiet_ms: u64) {
+    let (Some(user_at), Some(assistant_at)) = (state.last_user_at, state.last_assistant_at) else {
+        return;
+    };
+    if assistant_at < user_at || state.last_notified_assistant_at == Some(assistant_at) {
+        return;
+    }
+
+    state.cancel_pending();
+    let cancel = Arc::new(AtomicBool::new(false));
+    state.pending_cancel = Some(cancel.clone());
+    state.last_notified_assistant_at = Some(assistant_at);
+
+    let cwd = state.last_cwd.clone().unwrap_or_default();
+    let summary = state.last_assistant_content.clone();
+    let duration_ms = Some(assistant_at - user_at);
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(quiet_ms)).await;
+        if cancel.load(Ordering::Relaxed) { return; }
+        let _ = crate::notify::send_completion_notification("cursor", "Cursor", summary, None, duration_ms, cwd, None).await;
+    });
+}
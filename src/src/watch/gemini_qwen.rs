@@ -7,7 +7,16 @@ struct GeminiState {
     last_user_at: Option<i64>,
     last_gemini_at: Option<i64>,
     last_notified_gemini_at: Option<i64>,
+    last_gemini_content: Option<String>,
     confirm_notified_for_turn: bool,
+    turn_had_tool_use: bool,
+    // 用户消息之后还没有出现助手回复
+    awaiting_first_reply: bool,
+    // 最近一次确认提醒，用于去重；新的用户轮次清空
+    last_confirm_key: Option<String>,
+    last_confirm_at: Option<i64>,
+    // 最后一条消息的哈希，用于发现条数不变时的原地改写
+    last_message_hash: Option<u64>,
     // cancel flag for debounced notify timer
     pending_cancel: Option<Arc<AtomicBool>>,
 }
@@ -21,7 +30,13 @@ impl GeminiState {
             last_user_at: None,
             last_gemini_at: None,
             last_notified_gemini_at: None,
+            last_gemini_content: None,
             confirm_notified_for_turn: false,
+            turn_had_tool_use: false,
+            awaiting_first_reply: false,
+            last_confirm_key: None,
+            last_confirm_at: None,
+            last_message_hash: None,
             pending_cancel: None,
         }
     }
@@ -52,18 +67,65 @@ fn collect_gemini_message_jsons<'a>(content: &'a str, skip: usize) -> Option<(Ve
     Some((new_items, total_count))
 }
 
+// Windows 上经 PowerShell 重定向保存的会话文件可能是带 BOM 的 UTF-16，
+// 按 BOM 解码，否则按 UTF-8 处理并去掉 UTF-8 BOM
+fn decode_gemini_session_bytes(bytes: &[u8]) -> String {
+    let utf16 = |bytes: &[u8], from_bytes: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = bytes.chunks_exact(2).map(|pair| from_bytes([pair[0], pair[1]])).collect();
+        String::from_utf16_lossy(&units)
+    };
+    match bytes {
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8_lossy(rest).into_owned(),
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+fn read_gemini_session_file(path: &Path) -> std::io::Result<String> {
+    fs::read(path).map(|bytes| decode_gemini_session_bytes(&bytes))
+}
+
+fn gemini_message_hash(raw: &str) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    raw.hash(&mut hasher);
+    hasher.finish()
+}
+
+// confirm_detector 为 None 时不检测确认提示（如跟随新文件时的历史数据）
 fn process_gemini_messages_from_content(
     content: &str,
     skip: usize,
     state: &mut GeminiState,
     quiet_ms: u64,
+    confirm_detector: Option<&ConfirmDetector>,
 ) -> Option<usize> {
-    let (new_items, total_count) = collect_gemini_message_jsons(content, skip)?;
+    // 多取上次的最后一条，用于发现条数不变时的原地改写
+    let (items, total_count) = collect_gemini_message_jsons(content, skip.saturating_sub(1))?;
+    let new_hash = items.last().map(|raw| gemini_message_hash(raw));
+    let new_items = match items.split_first() {
+        Some((previous_last, rest)) if skip > 0 => {
+            let rewritten = state
+                .last_message_hash
+                .is_some_and(|hash| hash != gemini_message_hash(previous_last));
+            if rewritten {
+                // 流式输出的最终文本会改写最后一条，重新处理以刷新摘要并重置去抖
+                let msg = serde_json::from_str::<Value>(previous_last).ok()?;
+                if msg.get("type").and_then(|v| v.as_str()) == Some("gemini") {
+                    process_gemini_message(&msg, state, quiet_ms, confirm_detector);
+                }
+            }
+            rest
+        }
+        _ => &items[..],
+    };
 
     for raw in new_items {
         let msg = serde_json::from_str::<Value>(raw).ok()?;
-        process_gemini_message(&msg, state, quiet_ms);
+        process_gemini_message(&msg, state, quiet_ms, confirm_detector);
     }
+    state.last_message_hash = new_hash;
 
     Some(total_count)
 }
@@ -78,6 +140,9 @@ struct QwenSessionState {
     last_agent_content: Option<String>,
     last_cwd: Option<String>,
     confirm_notified_for_turn: bool,
+    // 最近一次确认提醒，用于去重
+    last_confirm_key: Option<String>,
+    last_confirm_at: Option<i64>,
 }
 
 impl QwenSessionState {
@@ -90,8 +155,14 @@ impl QwenSessionState {
             last_agent_content: None,
             last_cwd: None,
             confirm_notified_for_turn: false,
+            last_confirm_key: None,
+            last_confirm_at: None,
         }
     }
+
+    fn should_send_confirm(&mut self, prompt: &str, at: i64) -> bool {
+        should_send_confirm(&mut self.last_confirm_key, &mut self.last_confirm_at, prompt, at)
+    }
 }
 
 fn is_qwen_chat_file(full_path: &Path, name: &str) -> bool {
@@ -138,10 +209,82 @@ fn process_qwen_object(obj: &Value, _seed: bool, state: &mut QwenSessionState) {
     }
 }
 
+// 处理完一条新的助手记录后发出确认提醒或完成通知；实时监听与日志回放共用
+fn notify_qwen_assistant(obj: &Value, previous_assistant_at: Option<i64>, state: &mut QwenSessionState, confirm_detector: &ConfirmDetector) {
+    if obj.get("type").and_then(|v| v.as_str()) != Some("assistant") {
+        return;
+    }
+    let assistant_at = state.last_assistant_at.unwrap_or_else(now_unix_millis_i64);
+    let is_new_assistant = previous_assistant_at.map(|prev| assistant_at > prev).unwrap_or(true);
+    if !is_new_assistant || state.last_notified_assistant_at == Some(assistant_at) {
+        return;
+    }
+
+    let cwd = state.last_cwd.clone().unwrap_or_default();
+    let agent_content = state.last_agent_content.clone().unwrap_or_default();
+
+    if confirm_detector.enabled && confirm_detector.allows_turn(state.last_user_at, assistant_at) {
+        if let Some(prompt) = confirm_detector.detect(&agent_content) {
+            if state.should_send_confirm(&prompt, assistant_at) {
+                tauri::async_runtime::spawn(async move {
+                    let _ = crate::notify::send_notifications("qwen", &prompt, None, cwd, false, Some("confirm")).await;
+                });
+            }
+            state.last_notified_assistant_at = Some(assistant_at);
+            state.confirm_notified_for_turn = true;
+            return;
+        }
+    }
+
+    let duration_ms = state.last_user_at.map(|start| {
+        if assistant_at >= start { assistant_at - start } else { 0 }
+    });
+
+    let summary = state.last_agent_content.clone();
+    tauri::async_runtime::spawn(async move {
+        let _ = crate::notify::send_completion_notification("qwen", "Qwen", summary, None, duration_ms, cwd, None).await;
+    });
+    state.last_notified_assistant_at = Some(assistant_at);
+    state.confirm_notified_for_turn = true;
+}
+
+// 会话文件形如 <project>/chats/session-*.json
+fn is_gemini_session_file(full_path: &Path, name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.ends_with(".json")
+        && name.starts_with("session-")
+        && full_path.components().any(|c| c.as_os_str() == "chats")
+}
+
+// Gemini CLI 把工具调用记录在 toolCalls 中，也兼容 content 里的 functionCall 片段
+fn gemini_message_has_tool_call(msg: &Value) -> bool {
+    let has_tool_calls = msg
+        .get("toolCalls")
+        .and_then(|v| v.as_array())
+        .is_some_and(|calls| !calls.is_empty());
+    let has_function_call = msg
+        .get("content")
+        .and_then(|v| v.as_array())
+        .is_some_and(|parts| parts.iter().any(|part| part.get("functionCall").is_some()));
+    has_tool_calls || has_function_call
+}
+
+// 助手消息以确认提示结尾时返回需要提醒的提示。流式输出会反复改写同一条消息，
+// 同一提示在去重窗口内只返回一次
+fn gemini_confirm_prompt(state: &mut GeminiState, detector: &ConfirmDetector, at: i64) -> Option<String> {
+    if !detector.enabled || !detector.allows_turn(state.last_user_at, at) {
+        return None;
+    }
+    let prompt = detector.detect(state.last_gemini_content.as_deref()?)?;
+    state.confirm_notified_for_turn = true;
+    should_send_confirm(&mut state.last_confirm_key, &mut state.last_confirm_at, &prompt, at).then_some(prompt)
+}
+
 fn process_gemini_message(
     msg: &Value,
     state: &mut GeminiState,
     quiet_ms: u64,
+    confirm_detector: Option<&ConfirmDetector>,
 ) {
     let ts = msg.get("timestamp").and_then(parse_timestamp);
     let msg_type = msg.get("type").and_then(|v| v.as_str());
@@ -152,10 +295,33 @@ fn process_gemini_message(
             state.last_user_at = ts;
             state.last_gemini_at = None;
             state.last_notified_gemini_at = None;
+            state.last_gemini_content = None;
             state.confirm_notified_for_turn = false;
+            state.turn_had_tool_use = false;
+            state.awaiting_first_reply = true;
+            state.last_confirm_key = None;
+            state.last_confirm_at = None;
         }
         Some("gemini") => {
             state.last_gemini_at = ts;
+            state.turn_had_tool_use |= gemini_message_has_tool_call(msg);
+            let content = msg.get("content").map(extract_text_from_any).unwrap_or_default();
+            if !content.trim().is_empty() {
+                state.last_gemini_content = Some(compact_state_text(&content));
+            }
+
+            // 跟随新文件时读取的历史消息（detector 为 None）不发首次回复通知
+            if std::mem::take(&mut state.awaiting_first_reply) && confirm_detector.is_some() {
+                let summary = Some(content).filter(|text| !text.trim().is_empty()).map(|text| compact_state_text(&text));
+                spawn_first_reply_notification("gemini", "Gemini", summary, state.last_user_at, ts, String::new());
+            }
+
+            let at = ts.unwrap_or_else(now_unix_millis_i64);
+            if let Some(prompt) = confirm_detector.and_then(|detector| gemini_confirm_prompt(state, detector, at)) {
+                tauri::async_runtime::spawn(async move {
+                    let _ = crate::notify::send_notifications("gemini", &prompt, None, String::new(), false, Some("confirm")).await;
+                });
+            }
 
             if state.confirm_notified_for_turn {
                 state.cancel_pending();
@@ -169,6 +335,8 @@ fn process_gemini_message(
             let target_gemini_at = state.last_gemini_at;
             let user_at = state.last_user_at;
             let last_notified = state.last_notified_gemini_at;
+            let summary = state.last_gemini_content.clone();
+            let turn_had_tool_use = state.turn_had_tool_use;
 
             if last_notified == target_gemini_at {
                 return;
@@ -180,7 +348,9 @@ fn process_gemini_message(
                 let end_at = match target_gemini_at { Some(t) => t, None => return };
                 let start_at = match user_at { Some(t) => t, None => return };
                 let duration_ms = if end_at >= start_at { Some(end_at - start_at) } else { None };
-                let _ = crate::notify::send_notifications("gemini", "Gemini 任务已完成", duration_ms, String::new(), false, Some("complete")).await;
+                if !completion_allowed_for_tool_use("gemini", turn_had_tool_use) { return; }
+                if !completion_allowed_for_content(summary.as_deref()) { return; }
+                let _ = crate::notify::send_completion_notification("gemini", "Gemini", summary, None, duration_ms, String::new(), Some(turn_had_tool_use)).await;
             });
         }
         _ => {}
@@ -0,0 +1,77 @@
+// ============ 日志回放 ============
+
+// 把录制好的会话文件按记录时间戳（除以 speed）逐行喂给对应来源的处理逻辑，
+// 通知与实时监听一样真实发出，用于不启动 CLI 时复现误报。
+// 支持 claude / codex / qwen / cursor（JSONL）；aider 的聊天历史没有逐条时间戳，
+// 无法按原节奏回放，与 gemini / opencode 一样不支持。
+const REPLAY_MAX_GAP_MS: i64 = 10_000;
+
+fn replay_delay_ms(previous: Option<i64>, current: Option<i64>, speed: f64) -> u64 {
+    let (Some(previous), Some(current)) = (previous, current) else {
+        return 0;
+    };
+    let gap = (current - previous).clamp(0, REPLAY_MAX_GAP_MS) as f64;
+    (gap / speed.max(0.01)) as u64
+}
+
+pub async fn replay_log(source: &str, path: &Path, speed: f64) -> Result<usize, String> {
+    if !matches!(source, "claude" | "codex" | "qwen" | "cursor") {
+        return Err(format!("replay is not supported for source: {}", source));
+    }
+
+    let config = crate::config::load_config().unwrap_or_default();
+    let quiet_ms = |source_config: &crate::config::SourceConfig, default_ms: i32| {
+        (source_config.quiet_ms.unwrap_or(default_ms).max(0) as u64).max(3000)
+    };
+
+    // 与 test_confirm_detect 一样，无效的确认正则直接报错返回
+    let mut invalid = Vec::new();
+    let confirm_detector = ConfirmDetector::new(&config.confirm_alert, |msg| invalid.push(msg));
+    if !invalid.is_empty() {
+        return Err(invalid.join("\n"));
+    }
+
+    let mut objects = Vec::new();
+    read_jsonl_objects_from_offset(path, 0, |obj| objects.push(obj)).map_err(|e| e.to_string())?;
+
+    let mut claude_state = ClaudeState::new();
+    let mut codex_state = CodexSessionState::new();
+    let mut qwen_state = QwenSessionState::new();
+    let mut cursor_state = CursorSessionState::new();
+    let claude_quiet_ms = quiet_ms(&config.sources.claude, 3000);
+    let codex_quiet_ms = config.sources.codex.quiet_ms.unwrap_or(0).max(0) as u64;
+    let cursor_quiet_ms = quiet_ms(&config.sources.cursor, 3000);
+
+    let mut previous_ts = None;
+    for obj in &objects {
+        let ts = obj.get("timestamp").and_then(parse_timestamp);
+        let delay_ms = replay_delay_ms(previous_ts, ts, speed);
+        if delay_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+        }
+        previous_ts = ts.or(previous_ts);
+
+        match source {
+            "claude" => {
+                let prev_assistant_at = claude_state.last_assistant_at;
+                process_claude_object(obj, false, &mut claude_state);
+                if claude_state.last_assistant_at != prev_assistant_at {
+                    schedule_claude_notification(&mut claude_state, claude_quiet_ms, None);
+                }
+            }
+            "codex" => process_codex_object(obj, false, &mut codex_state, &confirm_detector, codex_quiet_ms),
+            "qwen" => {
+                let previous_assistant_at = qwen_state.last_assistant_at;
+                process_qwen_object(obj, false, &mut qwen_state);
+                notify_qwen_assistant(obj, previous_assistant_at, &mut qwen_state, &confirm_detector);
+            }
+            "cursor" => {
+                process_cursor_object(obj, &mut cursor_state);
+                schedule_cursor_notification(&mut cursor_state, cursor_quiet_ms);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(objects.len())
+}
@@ -1,58 +1,305 @@
 // ============ 主循环 ============
 
+// 去抖窗口优先级：sources.<name>.quiet_ms（配置文件） > 调用参数 > 默认值。
+// Claude / Gemini / Cursor / Aider 的窗口下限为 3000ms；Codex 为 0 时在 task_complete 后等待 token 宽限期（CODEX_TOKEN_GRACE_MS）再通知。
 pub fn start_watch<F>(
     sources: &str,
     interval_ms: i32,
     gemini_quiet_ms: i32,
     claude_quiet_ms: i32,
-    mut log_callback: F,
-) -> Result<Box<dyn FnOnce() + Send>, Box<dyn std::error::Error>>
+    codex_quiet_ms: i32,
+    control: WatchControl,
+    mut emit_log: F,
+) -> Result<StopHandle, Box<dyn std::error::Error>>
 where
     F: FnMut(String) + Send + 'static,
 {
+    // 日志同时写入 watch-logs/watch-{date}.log，窗口从未打开时也能保留记录
+    let log_writer = crate::watch_log::WatchLogWriter::new();
+    let mut log_callback = {
+        let log_writer = log_writer.clone();
+        move |line: String| {
+            log_writer.append(&line);
+            emit_log(line);
+        }
+    };
+
     let home = match get_home_dir() {
         Some(h) => h,
         None => return Err("Cannot find home directory".into()),
     };
 
-    let claude_root = home.join(CLAUDE_DIR);
-    let codex_root = home.join(CODEX_DIR);
-    let gemini_root = home.join(GEMINI_DIR);
-    let qwen_root = home.join(QWEN_DIR);
-
+    let config = crate::config::load_config().unwrap_or_default();
+
+    // 目录不存在时与默认路径一样直接跳过
+    let SourceRoots {
+        claude: mut claude_root,
+        codex: mut codex_root,
+        gemini: mut gemini_root,
+        qwen: mut qwen_root,
+        cursor: mut cursor_root,
+        opencode: mut opencode_dirs,
+        aider: mut aider_root,
+    } = SourceRoots::resolve(&home, &config);
+    let mut confirm_detector = ConfirmDetector::new(&config.confirm_alert, &mut log_callback);
+    let SourceFilters {
+        claude: mut claude_filter,
+        codex: mut codex_filter,
+        gemini: mut gemini_filter,
+        qwen: mut qwen_filter,
+        cursor: mut cursor_filter,
+        aider: mut aider_filter,
+    } = SourceFilters::new(&config, &mut log_callback);
+
+    let WatchControl { paused, debug } = control;
     let running = Arc::new(AtomicBool::new(true));
     let running_clone = running.clone();
 
-    let sources = normalize_sources(sources);
-    let claude_quiet_ms = (claude_quiet_ms.max(500) as u64).max(3000);
-    let gemini_quiet_ms = (gemini_quiet_ms.max(500) as u64).max(3000);
+    let requested_sources = normalize_sources(sources);
+    let mut sources = enabled_sources(&requested_sources, &config);
+    let mut claude_notify_subagents = config.sources.claude.notify_subagents;
+    let mut codex_require_header = config.sources.codex.require_session_header;
+    // 重新加载 settings.json 时仍以调用参数作为 quiet_ms 的默认值
+    let resolve_quiet = move |config: &crate::config::AppConfig| {
+        QuietWindows::resolve(config, claude_quiet_ms, gemini_quiet_ms, codex_quiet_ms)
+    };
+    let QuietWindows {
+        claude: mut claude_quiet_ms,
+        gemini: mut gemini_quiet_ms,
+        codex: mut codex_quiet_ms,
+        cursor: mut cursor_quiet_ms,
+        aider: mut aider_quiet_ms,
+        claude_catchup: mut claude_catchup_ms,
+        codex_catchup: mut codex_catchup_ms,
+        gemini_catchup: mut gemini_catchup_ms,
+    } = resolve_quiet(&config);
+    let log_retention_days = config.ui.watch_log_retention_days;
+    let mut stale_minutes = config.ui.watch_stale_minutes;
+    let mut source_config = config.sources.clone();
 
     tauri::async_runtime::spawn(async move {
-        let mut claude_state = ClaudeState::new();
+        let mut claude_states: HashMap<PathBuf, ClaudeState> = HashMap::new();
         let mut codex_states: HashMap<PathBuf, CodexSessionState> = HashMap::new();
+        let mut codex_headers = CodexHeaderCache::default();
         let mut gemini_state = GeminiState::new();
         let mut qwen_states: HashMap<PathBuf, QwenSessionState> = HashMap::new();
         let mut opencode_state = OpencodeState::new();
+        let mut aider_state = AiderState::new();
+        let mut cursor_file: Option<PathBuf> = None;
+        let mut cursor_state = CursorSessionState::new();
 
-        let mut tick_interval = interval(Duration::from_millis((interval_ms.max(500) as u64).max(1000)));
+        let mut tick_interval = interval(Duration::from_millis(clamp_watch_interval_ms(interval_ms)));
         let mut cleanup_counter = 0u32;
+        let settings_path = crate::config::get_settings_path();
+        let mut config_watcher = ConfigFileWatcher::new(&settings_path);
+        let mut stale_sources: HashSet<String> = HashSet::new();
 
-        log_callback(format!("[watch] started with sources: {:?}", sources));
+        log_callback(format!("[watch] started with sources: {:?} (requested: {:?})", sources, requested_sources));
 
         while running_clone.load(Ordering::Relaxed) {
             tick_interval.tick().await;
+            log_writer.flush();
+
+            // 等待 tick 期间可能已请求停止，避免停止后再跑一轮并重新跟随文件
+            if !running_clone.load(Ordering::Relaxed) {
+                break;
+            }
+
+            // 手动编辑 settings.json 后重新加载确认提醒配置，并通知前端刷新
+            if config_watcher.poll(&settings_path) {
+                match crate::config::load_config() {
+                    Ok(config) => {
+                        confirm_detector = ConfirmDetector::new(&config.confirm_alert, &mut log_callback);
+                        claude_notify_subagents = config.sources.claude.notify_subagents;
+                        codex_require_header = config.sources.codex.require_session_header;
+                        stale_minutes = config.ui.watch_stale_minutes;
+                        SourceRoots {
+                            claude: claude_root,
+                            codex: codex_root,
+                            gemini: gemini_root,
+                            qwen: qwen_root,
+                            cursor: cursor_root,
+                            opencode: opencode_dirs,
+                            aider: aider_root,
+                        } = SourceRoots::resolve(&home, &config);
+                        SourceFilters {
+                            claude: claude_filter,
+                            codex: codex_filter,
+                            gemini: gemini_filter,
+                            qwen: qwen_filter,
+                            cursor: cursor_filter,
+                            aider: aider_filter,
+                        } = SourceFilters::new(&config, &mut log_callback);
+                        QuietWindows {
+                            claude: claude_quiet_ms,
+                            gemini: gemini_quiet_ms,
+                            codex: codex_quiet_ms,
+                            cursor: cursor_quiet_ms,
+                            aider: aider_quiet_ms,
+                            claude_catchup: claude_catchup_ms,
+                            codex_catchup: codex_catchup_ms,
+                            gemini_catchup: gemini_catchup_ms,
+                        } = resolve_quiet(&config);
+                        log_callback("[watch] settings.json reloaded".to_string());
+
+                        // 启用或禁用的来源、log_dir 或 pattern 变化的来源重置状态：
+                        // 禁用时取消待发通知，重新启用或换了目录 / 匹配规则时从最新文件重新 seed
+                        let next_sources = enabled_sources(&requested_sources, &config);
+                        let watch_paths = |sources: &crate::config::SourcesConfig, source: &str| {
+                            sources.get(source).map(|s| (s.log_dir.clone(), s.pattern.clone()))
+                        };
+                        let moved: Vec<&str> = requested_sources
+                            .iter()
+                            .copied()
+                            .filter(|s| watch_paths(&source_config, s) != watch_paths(&config.sources, s))
+                            .collect();
+                        let changed: Vec<&str> = requested_sources
+                            .iter()
+                            .copied()
+                            .filter(|s| sources.contains(s) != next_sources.contains(s) || moved.contains(s))
+                            .collect();
+                        source_config = config.sources.clone();
+                        for source in &changed {
+                            match *source {
+                                "claude" => {
+                                    claude_states.values_mut().for_each(|state| state.cancel_pending());
+                                    claude_states.clear();
+                                }
+                                "codex" => {
+                                    codex_states.values_mut().for_each(|state| state.clear_pending_completion());
+                                    codex_states.clear();
+                                }
+                                "gemini" => {
+                                    gemini_state.cancel_pending();
+                                    gemini_state = GeminiState::new();
+                                }
+                                "qwen" => qwen_states.clear(),
+                                "opencode" => opencode_state = OpencodeState::new(),
+                                "cursor" => {
+                                    cursor_state.cancel_pending();
+                                    cursor_state = CursorSessionState::new();
+                                    cursor_file = None;
+                                }
+                                "aider" => {
+                                    aider_state.cancel_pending();
+                                    aider_state = AiderState::new();
+                                }
+                                _ => {}
+                            }
+                        }
+                        if !moved.is_empty() {
+                            log_callback(format!("[watch] log_dir / pattern changed: {:?}", moved));
+                        }
+                        if changed.iter().any(|s| !moved.contains(s)) {
+                            log_callback(format!("[watch] active sources changed: {:?}", next_sources));
+                        }
+                        sources = next_sources;
+                        crate::notify::emit_to_frontend("config-reloaded", config);
+                    }
+                    Err(e) => log_callback(format!("[watch] failed to reload settings.json: {}", e)),
+                }
+            }
+
+            // 记录上一轮结束时跟随的文件与读取位置
+            if let Ok(mut snapshot) = debug.lock() {
+                let file = FollowedFileDebug::new;
+                let now = now_unix_millis_i64();
+                let next: Vec<SourceWatchDebug> = requested_sources
+                    .iter()
+                    .map(|source| {
+                        let files = match *source {
+                            "claude" => claude_states.iter().map(|(p, s)| file(p, Some(s.last_file_size), None)).collect(),
+                            "codex" => codex_states.iter().map(|(p, s)| file(p, Some(s.processed_offset), None)).collect(),
+                            "gemini" => gemini_state.current_file.iter().map(|p| file(p, None, Some(gemini_state.last_count))).collect(),
+                            "qwen" => qwen_states.iter().map(|(p, s)| file(p, Some(s.processed_offset), None)).collect(),
+                            "opencode" => opencode_state.current_db.iter().map(|p| file(p, None, None)).collect(),
+                            "cursor" => cursor_file.iter().map(|p| file(p, Some(cursor_state.processed_offset), None)).collect(),
+                            "aider" => aider_state.current_file.iter().map(|p| file(p, Some(aider_state.processed_offset), None)).collect(),
+                            _ => Vec::new(),
+                        };
+                        let previous = snapshot.iter().find(|s| s.source == *source);
+                        SourceWatchDebug::next(previous, source, sources.contains(source), files, now)
+                    })
+                    .collect();
+                let running: Vec<(&str, bool)> = next.iter().map(|s| (s.source.as_str(), s.running)).collect();
+                crate::metrics::set_watch_running(&running);
+
+                // 来源刚进入停滞时记录日志并通知前端，恢复后才会再次提醒
+                if !paused.load(Ordering::Relaxed) {
+                    let stale_ms = i64::from(stale_minutes) * 60_000;
+                    for health in next.iter().map(|s| source_health(s, now, stale_ms)) {
+                        if health.stale && stale_sources.insert(health.source.clone()) {
+                            log_callback(format!(
+                                "[watch] {} has not processed new data for over {} minutes",
+                                health.source, stale_minutes
+                            ));
+                            crate::notify::emit_to_frontend("watch-stale", health);
+                        } else if !health.stale {
+                            stale_sources.remove(&health.source);
+                        }
+                    }
+                }
+                if !paused.load(Ordering::Relaxed) {
+                    emit_session_tail(&snapshot, &next);
+                }
+                *snapshot = next;
+            }
+
+            if paused.load(Ordering::Relaxed) {
+                // 暂停期间只把读取位置推进到末尾并取消待发通知，恢复后不会补发暂停期间的内容
+                let file_len = |path: &Path| safe_stat(path).map(|s| s.len()).unwrap_or(0);
+                for (path, state) in claude_states.iter_mut() {
+                    state.cancel_pending();
+                    state.last_file_size = file_len(path);
+                }
+                for (path, state) in codex_states.iter_mut() {
+                    state.clear_pending_completion();
+                    state.processed_offset = file_len(path);
+                }
+                // Gemini 按消息数增量处理，清空当前文件让恢复后重新 seed
+                gemini_state.cancel_pending();
+                gemini_state.current_file = None;
+                for (path, state) in qwen_states.iter_mut() {
+                    state.processed_offset = file_len(path);
+                }
+                opencode_state.current_db = None;
+                cursor_state.cancel_pending();
+                if let Some(path) = cursor_file.as_deref() {
+                    cursor_state.processed_offset = file_len(path);
+                }
+                aider_state.reset_turn();
+                if let Some(path) = aider_state.current_file.as_deref() {
+                    aider_state.processed_offset = file_len(path);
+                }
+                continue;
+            }
 
             // Monitor Claude
             if sources.contains(&"claude") && claude_root.exists() {
-                if let Some(latest_file) = find_latest_file(&claude_root, |_, name| name.to_lowercase().ends_with(".jsonl")) {
-                    if claude_state.current_file.as_ref() != Some(&latest_file) {
-                        claude_state.current_file = Some(latest_file.clone());
+                let latest = find_latest_files(&claude_root, |p, n| claude_filter.matches(p, n), get_claude_follow_top_n());
+
+                claude_states.retain(|path, state| {
+                    if latest.contains(path) {
+                        true
+                    } else {
+                        state.cancel_pending();
+                        false
+                    }
+                });
+
+                for latest_file in latest {
+                    let is_new_file = !claude_states.contains_key(&latest_file);
+                    let claude_state = claude_states.entry(latest_file.clone()).or_insert_with(ClaudeState::new);
+                    if is_new_file {
                         claude_state.reset_for_new_file();
                         log_callback(format!("[watch][claude] following {:?}", latest_file));
+                        emit_watch_event(WatchEvent::following("claude", &latest_file));
 
                         // Seed: read entire file with seed=true
                         if let Ok(offset) = read_jsonl_objects_from_offset(&latest_file, 0, |obj| {
-                            process_claude_object(&obj, true, &mut claude_state);
+                            if let Some(target) = claude_state.state_for_record(&obj, claude_notify_subagents) {
+                                process_claude_object(&obj, true, target);
+                            }
                         }) {
                             claude_state.last_file_size = offset;
                         }
@@ -61,18 +308,21 @@ where
                         if let (Some(user_at), Some(assistant_at)) = (claude_state.last_user_at, claude_state.last_assistant_at) {
                             if assistant_at >= user_at && !claude_state.notified_for_turn && !claude_state.confirm_notified_for_turn {
                                 let now = now_unix_millis_i64();
-                                let window_ms = (claude_quiet_ms * 2).max(15000) as i64;
-                                if now - assistant_at <= window_ms {
+                                if seed_within_catchup(assistant_at, now, claude_catchup_ms) {
                                     let had_tool_use = claude_state.last_assistant_had_tool_use;
                                     let adaptive_ms = if had_tool_use { claude_quiet_ms } else { claude_quiet_ms.min(15000) };
                                     let cancel = Arc::new(AtomicBool::new(false));
                                     claude_state.pending_cancel = Some(cancel.clone());
                                     let cwd = claude_state.last_cwd.clone().unwrap_or_default();
+                                    let summary = claude_state.last_assistant_content.clone();
+                                    let error = claude_state.last_error.clone();
+                                    let usage = claude_state.turn_token_usage();
                                     let duration_ms = assistant_at - user_at;
+                                    let turn_had_tool_use = claude_state.turn_had_tool_use;
                                     tauri::async_runtime::spawn(async move {
                                         tokio::time::sleep(Duration::from_millis(adaptive_ms)).await;
                                         if cancel.load(Ordering::Relaxed) { return; }
-                                        let _ = crate::notify::send_notifications("claude", "Claude 任务已完成", Some(duration_ms), cwd, false, Some("complete")).await;
+                                        send_claude_turn_notification(error, summary, usage, duration_ms, cwd, false, turn_had_tool_use).await;
                                     });
                                     claude_state.notified_for_turn = true;
                                     claude_state.confirm_notified_for_turn = true;
@@ -89,32 +339,14 @@ where
                         }
                         if current_size > claude_state.last_file_size {
                             if let Ok(offset) = read_jsonl_objects_from_offset(&latest_file, claude_state.last_file_size, |obj| {
-                                let prev_assistant_at = claude_state.last_assistant_at;
-                                process_claude_object(&obj, false, &mut claude_state);
-
-                                if claude_state.last_assistant_at != prev_assistant_at {
-                                    if let (Some(user_at), Some(assistant_at)) = (claude_state.last_user_at, claude_state.last_assistant_at) {
-                                        if assistant_at >= user_at {
-                                            // Always cancel old timer first (mirrors JS: clearTimeout before rescheduling)
-                                            claude_state.cancel_pending();
-                                            if !claude_state.confirm_notified_for_turn {
-                                                let had_tool_use = claude_state.last_assistant_had_tool_use;
-                                                let adaptive_ms = if had_tool_use { claude_quiet_ms } else { claude_quiet_ms.min(15000) };
-                                                let cancel = Arc::new(AtomicBool::new(false));
-                                                claude_state.pending_cancel = Some(cancel.clone());
-                                                let cwd = claude_state.last_cwd.clone().unwrap_or_default();
-                                                let duration_ms = assistant_at - user_at;
-                                                tauri::async_runtime::spawn(async move {
-                                                    tokio::time::sleep(Duration::from_millis(adaptive_ms)).await;
-                                                    if cancel.load(Ordering::Relaxed) { return; }
-                                                    let _ = crate::notify::send_notifications("claude", "Claude 任务已完成", Some(duration_ms), cwd, false, Some("complete")).await;
-                                                });
-                                                claude_state.notified_for_turn = true;
-                                                claude_state.confirm_notified_for_turn = true;
-                                                claude_state.last_notified_at = Some(assistant_at);
-                                                log_callback(format!("[watch][claude] notification scheduled ({}ms adaptive)", adaptive_ms));
-                                            }
-                                        }
+                                let Some(target) = claude_state.state_for_record(&obj, claude_notify_subagents) else { return; };
+                                let prev_assistant_at = target.last_assistant_at;
+                                process_claude_object(&obj, false, target);
+
+                                if target.last_assistant_at != prev_assistant_at {
+                                    if let Some(adaptive_ms) = schedule_claude_notification(target, claude_quiet_ms, Some(&latest_file)) {
+                                        let kind = if target.is_subagent { "subagent " } else { "" };
+                                        log_callback(format!("[watch][claude] {}notification scheduled ({}ms adaptive)", kind, adaptive_ms));
                                     }
                                 }
                             }) {
@@ -128,8 +360,11 @@ where
             // Monitor Codex
             if sources.contains(&"codex") && codex_root.exists() {
                 let follow_top_n = get_codex_follow_top_n();
-                let seed_catchup_ms = get_codex_seed_catchup_ms();
-                let latest = find_latest_files(&codex_root, |_, name| name.to_lowercase().ends_with(".jsonl"), follow_top_n);
+                let latest = find_latest_files(
+                    &codex_root,
+                    |p, n| codex_filter.matches(p, n) && (!codex_require_header || codex_headers.is_session_file(p)),
+                    follow_top_n,
+                );
 
                 codex_states.retain(|path, state| {
                     if latest.contains(path) {
@@ -145,7 +380,7 @@ where
                         let mut state = CodexSessionState::new();
 
                         if let Ok(offset) = read_jsonl_objects_from_offset(&file_path, 0, |obj| {
-                            process_codex_object(&obj, true, &mut state);
+                            process_codex_object(&obj, true, &mut state, &confirm_detector, codex_quiet_ms);
                         }) {
                             state.processed_offset = offset;
                         } else {
@@ -153,24 +388,25 @@ where
                         }
 
                         // Pass 2: seedCatchupMs — treat recent lines as live
-                        if seed_catchup_ms > 0 {
-                            let since = now_unix_millis_i64() - seed_catchup_ms as i64;
+                        if codex_catchup_ms > 0 {
+                            let since = now_unix_millis_i64() - codex_catchup_ms as i64;
                             let _ = read_jsonl_objects_from_offset(&file_path, 0, |obj| {
                                 let ts = obj.get("timestamp").and_then(parse_timestamp);
                                 if ts.map(|t| t >= since).unwrap_or(false) {
-                                    process_codex_object(&obj, false, &mut state);
+                                    process_codex_object(&obj, false, &mut state, &confirm_detector, codex_quiet_ms);
                                 }
                             });
                         }
 
                         log_callback(format!("[watch][codex] following {:?}", file_path));
+                        emit_watch_event(WatchEvent::following("codex", &file_path));
                         codex_states.insert(file_path.clone(), state);
                     } else if let Some(state) = codex_states.get_mut(&file_path) {
                         let file_size = safe_stat(&file_path).map(|stat| stat.len()).unwrap_or(0);
                         state.processed_offset = normalize_processed_offset(file_size, state.processed_offset);
 
                         if let Ok(offset) = read_jsonl_objects_from_offset(&file_path, state.processed_offset, |obj| {
-                            process_codex_object(&obj, false, state);
+                            process_codex_object(&obj, false, state, &confirm_detector, codex_quiet_ms);
                         }) {
                             state.processed_offset = offset;
                         }
@@ -179,74 +415,77 @@ where
             }
 
             // Monitor Gemini
-            if sources.contains(&"gemini") && gemini_root.exists() {
-                if let Some(latest_file) = find_latest_file(&gemini_root, |full_path, name| {
-                    if !name.to_lowercase().ends_with(".json") {
-                        return false;
-                    }
-                    if !name.to_lowercase().starts_with("session-") {
-                        return false;
-                    }
-                    full_path.components().any(|c| c.as_os_str() == "chats")
-                }) {
-                    let stat = match safe_stat(&latest_file) {
-                        Some(s) => s,
-                        None => continue,
-                    };
-
-                    let mtime_ms = stat
-                        .modified()
-                        .ok()
-                        .and_then(|m| m.duration_since(SystemTime::UNIX_EPOCH).ok())
-                        .map(|d| d.as_millis() as u64)
-                        .unwrap_or(0);
-
-                    if gemini_state.current_file.as_ref() != Some(&latest_file) {
-                        gemini_state.cancel_pending();
-                        gemini_state.current_file = Some(latest_file.clone());
-                        gemini_state.current_mtime_ms = mtime_ms;
-                        gemini_state.last_user_at = None;
-                        gemini_state.last_gemini_at = None;
-                        gemini_state.last_notified_gemini_at = None;
-                        gemini_state.confirm_notified_for_turn = false;
-
-                        if let Ok(content) = fs::read_to_string(&latest_file) {
-                            if let Some(total_count) = process_gemini_messages_from_content(
-                                &content,
-                                0,
-                                &mut gemini_state,
-                                gemini_quiet_ms,
-                            ) {
-                                gemini_state.last_count = total_count;
-                                // After seeding, mark notified so we don't re-fire on old data
-                                gemini_state.last_notified_gemini_at = gemini_state.last_gemini_at;
-                                gemini_state.cancel_pending();
-                                log_callback(format!("[watch][gemini] following {:?}", latest_file));
+            // 用带标签的块提前结束 Gemini 分支，避免 continue 跳过后续来源和清理
+            'gemini: {
+                if sources.contains(&"gemini") && gemini_root.exists() {
+                    if let Some(latest_file) = find_latest_file(&gemini_root, |p, n| gemini_filter.matches(p, n)) {
+                        let stat = match safe_stat(&latest_file) {
+                            Some(s) => s,
+                            None => break 'gemini,
+                        };
+
+                        let mtime_ms = stat
+                            .modified()
+                            .ok()
+                            .and_then(|m| m.duration_since(SystemTime::UNIX_EPOCH).ok())
+                            .map(|d| d.as_millis() as u64)
+                            .unwrap_or(0);
+
+                        if gemini_state.current_file.as_ref() != Some(&latest_file) {
+                            gemini_state.cancel_pending();
+                            gemini_state.current_file = Some(latest_file.clone());
+                            gemini_state.current_mtime_ms = mtime_ms;
+                            gemini_state.last_user_at = None;
+                            gemini_state.last_gemini_at = None;
+                            gemini_state.last_notified_gemini_at = None;
+                            gemini_state.confirm_notified_for_turn = false;
+
+                            if let Ok(content) = read_gemini_session_file(&latest_file) {
+                                if let Some(total_count) = process_gemini_messages_from_content(
+                                    &content,
+                                    0,
+                                    &mut gemini_state,
+                                    gemini_quiet_ms,
+                                    None,
+                                ) {
+                                    gemini_state.last_count = total_count;
+                                    // 最后一条回复在补发窗口内时保留 seed 期间排好的通知，否则标记为已通知避免重复
+                                    let catch_up = gemini_state
+                                        .last_gemini_at
+                                        .is_some_and(|at| seed_within_catchup(at, now_unix_millis_i64(), gemini_catchup_ms));
+                                    if !catch_up {
+                                        gemini_state.last_notified_gemini_at = gemini_state.last_gemini_at;
+                                        gemini_state.cancel_pending();
+                                    }
+                                    log_callback(format!("[watch][gemini] following {:?}", latest_file));
+                                    emit_watch_event(WatchEvent::following("gemini", &latest_file));
+                                }
                             }
+                            break 'gemini;
                         }
-                        continue;
-                    }
 
-                    if mtime_ms <= gemini_state.current_mtime_ms {
-                        continue;
-                    }
+                        if mtime_ms <= gemini_state.current_mtime_ms {
+                            break 'gemini;
+                        }
+
+                        let content = match read_gemini_session_file(&latest_file) {
+                            Ok(c) => c,
+                            Err(_) => break 'gemini,
+                        };
+
+                        let Some(total_count) = process_gemini_messages_from_content(
+                            &content,
+                            gemini_state.last_count,
+                            &mut gemini_state,
+                            gemini_quiet_ms,
+                            Some(&confirm_detector),
+                        ) else {
+                            break 'gemini;
+                        };
 
-                    let content = match fs::read_to_string(&latest_file) {
-                        Ok(c) => c,
-                        Err(_) => continue,
-                    };
-
-                    let Some(total_count) = process_gemini_messages_from_content(
-                        &content,
-                        gemini_state.last_count,
-                        &mut gemini_state,
-                        gemini_quiet_ms,
-                    ) else {
-                        continue;
-                    };
-
-                    gemini_state.current_mtime_ms = mtime_ms;
-                    gemini_state.last_count = total_count;
+                        gemini_state.current_mtime_ms = mtime_ms;
+                        gemini_state.last_count = total_count;
+                    }
                 }
             }
 
@@ -254,7 +493,7 @@ where
             // Monitor Qwen
             if sources.contains(&"qwen") && qwen_root.exists() {
                 let follow_top_n = get_qwen_follow_top_n();
-                let latest = find_latest_files(&qwen_root, is_qwen_chat_file, follow_top_n);
+                let latest = find_latest_files(&qwen_root, |p, n| qwen_filter.matches(p, n), follow_top_n);
 
                 for file_path in &latest {
                     if !qwen_states.contains_key(file_path) {
@@ -267,6 +506,7 @@ where
                         }
 
                         log_callback(format!("[watch][qwen] following {:?}", file_path));
+                        emit_watch_event(WatchEvent::following("qwen", file_path));
                         qwen_states.insert(file_path.clone(), state);
                     }
                 }
@@ -283,37 +523,7 @@ where
                     if let Ok(offset) = read_jsonl_objects_from_offset(&file_path, state.processed_offset, |obj| {
                         let previous_assistant_at = state.last_assistant_at;
                         process_qwen_object(&obj, false, state);
-
-                        if obj.get("type").and_then(|v| v.as_str()) == Some("assistant") {
-                            let assistant_at = state.last_assistant_at.unwrap_or_else(now_unix_millis_i64);
-                            let is_new_assistant = previous_assistant_at.map(|prev| assistant_at > prev).unwrap_or(true);
-
-                            if is_new_assistant && state.last_notified_assistant_at != Some(assistant_at) {
-                                let cwd = state.last_cwd.clone().unwrap_or_default();
-                                let agent_content = state.last_agent_content.clone().unwrap_or_default();
-
-                                if is_confirm_alert_enabled() {
-                                    if let Some(prompt) = detect_turn_end_confirm_prompt(&agent_content) {
-                                        tauri::async_runtime::spawn(async move {
-                                            let _ = crate::notify::send_notifications("qwen", &prompt, None, cwd, false, Some("confirm")).await;
-                                        });
-                                        state.last_notified_assistant_at = Some(assistant_at);
-                                        state.confirm_notified_for_turn = true;
-                                        return;
-                                    }
-                                }
-
-                                let duration_ms = state.last_user_at.map(|start| {
-                                    if assistant_at >= start { assistant_at - start } else { 0 }
-                                });
-
-                                tauri::async_runtime::spawn(async move {
-                                    let _ = crate::notify::send_notifications("qwen", "Qwen 任务已完成", duration_ms, cwd, false, Some("complete")).await;
-                                });
-                                state.last_notified_assistant_at = Some(assistant_at);
-                                state.confirm_notified_for_turn = true;
-                            }
-                        }
+                        notify_qwen_assistant(&obj, previous_assistant_at, state, &confirm_detector);
                     }) {
                         state.processed_offset = offset;
                     }
@@ -322,10 +532,11 @@ where
 
             // Monitor OpenCode
             if sources.contains(&"opencode") {
-                if let Some(db_path) = find_latest_opencode_db(&home) {
+                if let Some(db_path) = find_latest_opencode_db(&opencode_dirs) {
                     let is_new_db = opencode_state.current_db.as_ref() != Some(&db_path);
                     if is_new_db {
                         log_callback(format!("[watch][opencode] following {:?}", db_path));
+                        emit_watch_event(WatchEvent::following("opencode", &db_path));
                     }
 
                     let scan_limit = get_opencode_scan_limit();
@@ -335,13 +546,14 @@ where
                                 let cwd = completion.cwd.clone();
                                 let duration_ms = completion.duration_ms;
                                 tauri::async_runtime::spawn(async move {
-                                    let _ = crate::notify::send_notifications(
+                                    let _ = crate::notify::send_completion_notification(
                                         "opencode",
-                                        "OpenCode 任务已完成",
+                                        "OpenCode",
+                                        None,
+                                        None,
                                         duration_ms,
                                         cwd,
-                                        false,
-                                        Some("complete"),
+                                        None,
                                     )
                                     .await;
                                 });
@@ -349,7 +561,65 @@ where
                         }
                         Err(err) => {
                             log_callback(format!("[watch][opencode] failed to scan {:?}: {}", db_path, err));
+                            emit_watch_event(WatchEvent::Error {
+                                source: "opencode".to_string(),
+                                message: format!("failed to scan {:?}: {}", db_path, err),
+                            });
+                        }
+                    }
+                }
+            }
+
+            // Monitor Cursor
+            if sources.contains(&"cursor") && cursor_root.exists() {
+                if let Some(latest_file) = find_latest_file(&cursor_root, |p, n| cursor_filter.matches(p, n)) {
+                    if cursor_file.as_ref() != Some(&latest_file) {
+                        cursor_state.cancel_pending();
+                        cursor_state = CursorSessionState::new();
+                        cursor_file = Some(latest_file.clone());
+
+                        // Seed: 读取历史，只记录状态，不补发旧通知
+                        if let Ok(offset) = read_jsonl_objects_from_offset(&latest_file, 0, |obj| {
+                            process_cursor_object(&obj, &mut cursor_state);
+                        }) {
+                            cursor_state.processed_offset = offset;
+                        }
+                        cursor_state.last_notified_assistant_at = cursor_state.last_assistant_at;
+                        log_callback(format!("[watch][cursor] following {:?}", latest_file));
+                        emit_watch_event(WatchEvent::following("cursor", &latest_file));
+                    } else {
+                        let file_size = safe_stat(&latest_file).map(|s| s.len()).unwrap_or(0);
+                        cursor_state.processed_offset = normalize_processed_offset(file_size, cursor_state.processed_offset);
+                        if let Ok(offset) = read_jsonl_objects_from_offset(&latest_file, cursor_state.processed_offset, |obj| {
+                            process_cursor_object(&obj, &mut cursor_state);
+                        }) {
+                            cursor_state.processed_offset = offset;
+                        }
+                        schedule_cursor_notification(&mut cursor_state, cursor_quiet_ms);
+                    }
+                }
+            }
+
+            // Monitor Aider
+            if sources.contains(&"aider") {
+                if let Some(latest_file) = aider_root.as_deref().and_then(|root| find_latest_file(root, |p, n| aider_filter.matches(p, n))) {
+                    if aider_state.current_file.as_ref() != Some(&latest_file) {
+                        // 只关注开始跟随之后追加的内容
+                        aider_state.reset_turn();
+                        aider_state.current_file = Some(latest_file.clone());
+                        aider_state.processed_offset = safe_stat(&latest_file).map(|s| s.len()).unwrap_or(0);
+                        log_callback(format!("[watch][aider] following {:?}", latest_file));
+                        emit_watch_event(WatchEvent::following("aider", &latest_file));
+                    } else {
+                        let file_size = safe_stat(&latest_file).map(|s| s.len()).unwrap_or(0);
+                        aider_state.processed_offset = normalize_processed_offset(file_size, aider_state.processed_offset);
+                        let now = now_unix_millis_i64();
+                        if let Ok(offset) = read_text_lines_from_offset(&latest_file, aider_state.processed_offset, |line| {
+                            process_aider_line(line, now, &mut aider_state);
+                        }) {
+                            aider_state.processed_offset = offset;
                         }
+                        schedule_aider_notification(&mut aider_state, aider_quiet_ms);
                     }
                 }
             }
@@ -357,16 +627,158 @@ where
             cleanup_counter += 1;
             if cleanup_counter >= 60 {
                 cleanup_counter = 0;
-                // 可以在这里添加清理逻辑
+                crate::watch_log::cleanup_watch_logs(log_retention_days);
             }
         }
 
+        if let Ok(mut snapshot) = debug.lock() {
+            snapshot.clear();
+        }
+        crate::metrics::clear_watch_running();
         log_callback("[watch] stopped".to_string());
+        log_writer.flush();
     });
 
-    Ok(Box::new(move || {
-        running.store(false, Ordering::Relaxed);
-    }))
+    Ok(StopHandle::new(running))
+}
+
+// 各来源的日志根目录：配置的 log_dir 优先，其次环境变量，最后是默认路径
+struct SourceRoots {
+    claude: PathBuf,
+    codex: PathBuf,
+    gemini: PathBuf,
+    qwen: PathBuf,
+    cursor: PathBuf,
+    opencode: Vec<PathBuf>,
+    aider: Option<PathBuf>,
+}
+
+// 各来源的候选文件筛选规则（sources.<name>.pattern 或默认规则）
+struct SourceFilters {
+    claude: CandidateFilter,
+    codex: CandidateFilter,
+    gemini: CandidateFilter,
+    qwen: CandidateFilter,
+    cursor: CandidateFilter,
+    aider: CandidateFilter,
+}
+
+impl SourceFilters {
+    fn new<L>(config: &crate::config::AppConfig, mut log: L) -> Self
+    where
+        L: FnMut(String),
+    {
+        Self {
+            claude: CandidateFilter::new("claude", config, is_jsonl_file, &mut log),
+            codex: CandidateFilter::new("codex", config, is_jsonl_file, &mut log),
+            gemini: CandidateFilter::new("gemini", config, is_gemini_session_file, &mut log),
+            qwen: CandidateFilter::new("qwen", config, is_qwen_chat_file, &mut log),
+            cursor: CandidateFilter::new("cursor", config, is_jsonl_file, &mut log),
+            aider: CandidateFilter::new("aider", config, is_aider_history_file, &mut log),
+        }
+    }
+}
+
+// 生效的去抖窗口与 seed 补发窗口（毫秒）
+struct QuietWindows {
+    claude: u64,
+    gemini: u64,
+    codex: u64,
+    cursor: u64,
+    aider: u64,
+    claude_catchup: u64,
+    codex_catchup: u64,
+    gemini_catchup: u64,
+}
+
+impl QuietWindows {
+    fn resolve(config: &crate::config::AppConfig, claude_quiet_ms: i32, gemini_quiet_ms: i32, codex_quiet_ms: i32) -> Self {
+        let sources = &config.sources;
+        let claude = (sources.claude.quiet_ms.unwrap_or(claude_quiet_ms).max(500) as u64).max(3000);
+        Self {
+            claude,
+            gemini: (sources.gemini.quiet_ms.unwrap_or(gemini_quiet_ms).max(500) as u64).max(3000),
+            codex: sources.codex.quiet_ms.unwrap_or(codex_quiet_ms).max(0) as u64,
+            cursor: (sources.cursor.quiet_ms.unwrap_or(3000).max(500) as u64).max(3000),
+            aider: (sources.aider.quiet_ms.unwrap_or(3000).max(500) as u64).max(3000),
+            claude_catchup: seed_catchup_ms(&sources.claude, (claude * 2).max(15000)),
+            codex_catchup: seed_catchup_ms(&sources.codex, get_codex_seed_catchup_ms()),
+            gemini_catchup: seed_catchup_ms(&sources.gemini, 0),
+        }
+    }
+}
+
+impl SourceRoots {
+    fn resolve(home: &Path, config: &crate::config::AppConfig) -> Self {
+        let sources = &config.sources;
+        Self {
+            claude: resolve_source_root(home, sources.claude.log_dir.as_deref(), Some(("CLAUDE_CONFIG_DIR", "projects")), CLAUDE_DIR),
+            codex: resolve_source_root(home, sources.codex.log_dir.as_deref(), Some(("CODEX_HOME", "sessions")), CODEX_DIR),
+            gemini: resolve_source_root(home, sources.gemini.log_dir.as_deref(), None, GEMINI_DIR),
+            qwen: resolve_source_root(home, sources.qwen.log_dir.as_deref(), None, QWEN_DIR),
+            cursor: resolve_source_root(home, sources.cursor.log_dir.as_deref(), None, CURSOR_DIR),
+            opencode: match sources.opencode.log_dir.as_deref().map(str::trim).filter(|d| !d.is_empty()) {
+                Some(dir) => vec![expand_home_path(home, dir)],
+                None => get_opencode_data_dirs(home),
+            },
+            aider: get_aider_watch_root(home, sources.aider.log_dir.as_deref()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceDetection {
+    pub source: String,
+    pub installed: bool,
+    pub log_dir: String,
+    pub recent_file: Option<String>,
+}
+
+// 检查各来源的日志目录是否存在以及最近的会话文件，用于在界面提示"未检测到"
+pub fn detect_sources() -> Vec<SourceDetection> {
+    let Some(home) = get_home_dir() else { return Vec::new(); };
+    let config = crate::config::load_config().unwrap_or_default();
+    let roots = SourceRoots::resolve(&home, &config);
+
+    let detect = |source: &str, root: &Path, is_candidate: fn(&Path, &str) -> bool| {
+        let filter = CandidateFilter::new(source, &config, is_candidate, |_| {});
+        SourceDetection {
+            source: source.to_string(),
+            installed: root.is_dir(),
+            log_dir: root.to_string_lossy().to_string(),
+            recent_file: find_latest_file(root, |p, n| filter.matches(p, n)).map(|p| p.to_string_lossy().to_string()),
+        }
+    };
+
+    let opencode_db = find_latest_opencode_db(&roots.opencode);
+    let opencode_dir = opencode_db
+        .as_deref()
+        .and_then(Path::parent)
+        .or_else(|| roots.opencode.iter().find(|dir| dir.is_dir()).map(PathBuf::as_path))
+        .or_else(|| roots.opencode.first().map(PathBuf::as_path));
+    let aider_filter = CandidateFilter::new("aider", &config, is_aider_history_file, |_| {});
+    let aider_file = roots.aider.as_deref().and_then(|root| find_latest_file(root, |p, n| aider_filter.matches(p, n)));
+
+    vec![
+        detect("claude", &roots.claude, is_jsonl_file),
+        detect("codex", &roots.codex, is_jsonl_file),
+        detect("gemini", &roots.gemini, is_gemini_session_file),
+        detect("qwen", &roots.qwen, is_qwen_chat_file),
+        SourceDetection {
+            source: "opencode".to_string(),
+            installed: opencode_dir.is_some_and(Path::is_dir),
+            log_dir: opencode_dir.map(|dir| dir.to_string_lossy().to_string()).unwrap_or_default(),
+            recent_file: opencode_db.as_ref().map(|p| p.to_string_lossy().to_string()),
+        },
+        detect("cursor", &roots.cursor, is_jsonl_file),
+        // Aider 的历史文件在各项目目录下，找到历史文件才算检测到
+        SourceDetection {
+            source: "aider".to_string(),
+            installed: aider_file.is_some(),
+            log_dir: roots.aider.as_ref().map(|dir| dir.to_string_lossy().to_string()).unwrap_or_default(),
+            recent_file: aider_file.as_ref().map(|p| p.to_string_lossy().to_string()),
+        },
+    ]
 }
 
 fn get_home_dir() -> Option<PathBuf> {
@@ -13,6 +13,15 @@ fn get_codex_seed_catchup_ms() -> u64 {
         .unwrap_or(30000)
 }
 
+// sources.<name>.seed_catchup_ms 为空时使用来源原有的默认窗口
+fn seed_catchup_ms(source_config: &crate::config::SourceConfig, default_ms: u64) -> u64 {
+    source_config.seed_catchup_ms.map(|ms| ms.max(0) as u64).unwrap_or(default_ms)
+}
+
+fn seed_within_catchup(at: i64, now: i64, catchup_ms: u64) -> bool {
+    catchup_ms > 0 && now - at <= catchup_ms as i64
+}
+
 fn is_confirm_alert_enabled() -> bool {
     std::env::var("WATCH_CONFIRM_ALERT_ENABLED")
         .ok()
@@ -27,9 +36,163 @@ const CLAUDE_DIR: &str = ".claude/projects";
 const CODEX_DIR: &str = ".codex/sessions";
 const GEMINI_DIR: &str = ".gemini/tmp";
 const QWEN_DIR: &str = ".qwen/projects";
+const CURSOR_DIR: &str = ".cursor/chats";
+const AIDER_HISTORY_FILE: &str = ".aider.chat.history.md";
 const MAX_STATE_TEXT_CHARS: usize = 4096;
 const MAX_OPENCODE_SEEN_MESSAGE_IDS: usize = 2048;
 
+// 通过 watch-event 通道发给前端的结构化事件，watch-log 仍保留纯文本日志
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WatchEvent {
+    Following { source: String, file: String },
+    Completion { source: String, duration_ms: Option<i64>, summary: Option<String> },
+    Confirm { source: String, prompt: String },
+    Error { source: String, message: String },
+}
+
+impl WatchEvent {
+    fn following(source: &str, file: &Path) -> Self {
+        WatchEvent::Following {
+            source: source.to_string(),
+            file: file.to_string_lossy().to_string(),
+        }
+    }
+}
+
+pub fn emit_watch_event(event: WatchEvent) {
+    crate::notify::emit_to_frontend("watch-event", event);
+}
+
+// 监听循环每轮更新的只读快照，供 get_watch_debug 排查"为什么没有收到通知"
+#[derive(Debug, Clone, Serialize)]
+pub struct FollowedFileDebug {
+    pub path: String,
+    pub modified_at: Option<i64>,
+    // 按字节读取的来源记录读取位置；Gemini 记录已处理的消息数
+    pub processed_offset: Option<u64>,
+    pub processed_messages: Option<usize>,
+}
+
+impl FollowedFileDebug {
+    fn new(path: &Path, processed_offset: Option<u64>, processed_messages: Option<usize>) -> Self {
+        Self {
+            path: path.to_string_lossy().to_string(),
+            modified_at: file_mtime_millis(path).map(|ms| ms as i64),
+            processed_offset,
+            processed_messages,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceWatchDebug {
+    pub source: String,
+    pub running: bool,
+    pub files: Vec<FollowedFileDebug>,
+    // 最近一次读取位置前进（或开始跟随新文件）的时间，以及本轮快照的时间
+    pub last_activity_at: i64,
+    pub checked_at: i64,
+}
+
+impl SourceWatchDebug {
+    // 跟随的文件与读取位置都没变时沿用上一轮的活动时间
+    fn next(previous: Option<&SourceWatchDebug>, source: &str, running: bool, files: Vec<FollowedFileDebug>, now: i64) -> Self {
+        let progress = |files: &[FollowedFileDebug]| -> Vec<(String, Option<u64>, Option<usize>)> {
+            files.iter().map(|f| (f.path.clone(), f.processed_offset, f.processed_messages)).collect()
+        };
+        let last_activity_at = match previous {
+            Some(prev) if progress(&prev.files) == progress(&files) => prev.last_activity_at,
+            _ => now,
+        };
+        Self { source: source.to_string(), running, files, last_activity_at, checked_at: now }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceHealth {
+    pub source: String,
+    pub last_activity_at: i64,
+    pub latest_file_modified_at: Option<i64>,
+    pub stale: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchHealth {
+    pub running: bool,
+    pub paused: bool,
+    pub stale_minutes: u32,
+    pub sources: Vec<SourceHealth>,
+}
+
+// 跟随的文件在最近一次活动之后仍有写入、且超过阈值没有读到任何内容时视为停滞；
+// 快照本身超过阈值没有更新说明监听循环已经退出。stale_ms 为 0 时不检查
+pub fn source_health(debug: &SourceWatchDebug, now: i64, stale_ms: i64) -> SourceHealth {
+    // OpenCode 不记录读取位置，无法判断是否漏读
+    let latest_file_modified_at = debug
+        .files
+        .iter()
+        .filter(|f| f.processed_offset.is_some() || f.processed_messages.is_some())
+        .filter_map(|f| f.modified_at)
+        .max();
+    let stale = debug.running
+        && stale_ms > 0
+        && (now - debug.checked_at > stale_ms
+            || (now - debug.last_activity_at > stale_ms
+                && latest_file_modified_at.is_some_and(|modified| modified > debug.last_activity_at)));
+    SourceHealth {
+        source: debug.source.clone(),
+        last_activity_at: debug.last_activity_at,
+        latest_file_modified_at,
+        stale,
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchDebugInfo {
+    pub running: bool,
+    pub paused: bool,
+    pub sources: Vec<SourceWatchDebug>,
+}
+
+pub type WatchDebugHandle = Arc<std::sync::Mutex<Vec<SourceWatchDebug>>>;
+
+// 与前端命令共享的监听状态：暂停开关与调试快照
+pub struct WatchControl {
+    pub paused: Arc<AtomicBool>,
+    pub debug: WatchDebugHandle,
+}
+
+// 监听任务的停止句柄；显式 stop 或被丢弃时都会通知任务退出，避免遗留孤儿任务
+pub struct StopHandle {
+    running: Arc<AtomicBool>,
+}
+
+impl StopHandle {
+    fn new(running: Arc<AtomicBool>) -> Self {
+        Self { running }
+    }
+
+    pub fn stop(self) {
+        // 实际的停止信号在 Drop 中发出
+    }
+}
+
+impl Drop for StopHandle {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+// 同时跟随的 Claude 会话文件数；设为 1 时只跟随最新的会话
+fn get_claude_follow_top_n() -> usize {
+    std::env::var("CLAUDE_FOLLOW_TOP_N")
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(5)
+}
+
 fn get_codex_follow_top_n() -> usize {
     std::env::var("CODEX_FOLLOW_TOP_N")
         .ok()
@@ -75,6 +238,32 @@ fn is_codex_work_type(type_str: &str) -> bool {
     matches!(type_str, "reasoning" | "function_call" | "function_call_output" | "custom_tool_call" | "custom_tool_call_output" | "web_search_call" | "tool_use")
 }
 
+// RFC3339 之外的常见写法：空格分隔（可带时区偏移），或 RFC2822；不带时区时按本地时间处理
+fn parse_loose_datetime(text: &str) -> Option<i64> {
+    const OFFSET_FORMATS: &[&str] = &["%Y-%m-%d %H:%M:%S%.f%:z", "%Y-%m-%d %H:%M:%S%.f%z"];
+    const NAIVE_FORMATS: &[&str] = &["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"];
+
+    if let Some(dt) = OFFSET_FORMATS
+        .iter()
+        .find_map(|fmt| chrono::DateTime::parse_from_str(text, fmt).ok())
+    {
+        return Some(dt.timestamp_millis());
+    }
+    if let Some(naive) = NAIVE_FORMATS
+        .iter()
+        .find_map(|fmt| chrono::NaiveDateTime::parse_from_str(text, fmt).ok())
+    {
+        use chrono::TimeZone;
+        return chrono::Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|dt| dt.timestamp_millis());
+    }
+    chrono::DateTime::parse_from_rfc2822(text)
+        .ok()
+        .map(|dt| dt.timestamp_millis())
+}
+
 fn parse_timestamp(value: &Value) -> Option<i64> {
     // 处理字符串时间戳
     if let Some(s) = value.as_str() {
@@ -86,6 +275,9 @@ fn parse_timestamp(value: &Value) -> Option<i64> {
         if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(trimmed) {
             return Some(dt.timestamp_millis());
         }
+        if let Some(ms) = parse_loose_datetime(trimmed) {
+            return Some(ms);
+        }
         // 尝试纯数字字符串
         if let Ok(num) = trimmed.parse::<f64>() {
             if num.is_finite() {
@@ -145,33 +337,33 @@ where
     let mut file = File::open(path)?;
     file.seek(SeekFrom::Start(offset))?;
 
+    // 按字节读取：未写完的行（可能截断在多字节字符中间）留到下次从行首重读，
+    // 不会因为半个 UTF-8 字符报错或被替换成乱码
     let mut reader = BufReader::new(file);
-    let mut line = String::new();
+    let mut buf = Vec::new();
     let mut next_offset = offset;
 
     loop {
-        line.clear();
-        let line_start_offset = reader.stream_position()?;
-        let bytes_read = reader.read_line(&mut line)?;
-        if bytes_read == 0 {
+        buf.clear();
+        let bytes_read = reader.read_until(b'\n', &mut buf)?;
+        if bytes_read == 0 || buf.last() != Some(&b'\n') {
             break;
         }
 
-        if !line.ends_with('\n') {
-            next_offset = line_start_offset;
-            break;
-        }
-
-        if let Some(obj) = safe_json_parse(&line) {
+        if let Some(obj) = safe_json_parse(&String::from_utf8_lossy(&buf)) {
             visit(obj);
         }
 
-        next_offset = reader.stream_position()?;
+        next_offset += bytes_read as u64;
     }
 
     Ok(next_offset)
 }
 
+fn is_jsonl_file(_full_path: &Path, name: &str) -> bool {
+    name.to_lowercase().ends_with(".jsonl")
+}
+
 fn normalize_processed_offset(file_size: u64, processed_offset: u64) -> u64 {
     if processed_offset > file_size {
         0
@@ -197,6 +389,45 @@ fn file_mtime_millis(path: &Path) -> Option<u128> {
     })
 }
 
+// 会话文件筛选：配置了 sources.<name>.pattern 时按 glob 匹配文件名（不区分大小写），
+// 否则使用来源的默认规则
+struct CandidateFilter {
+    pattern: Option<glob::Pattern>,
+    default: fn(&Path, &str) -> bool,
+}
+
+impl CandidateFilter {
+    fn new<L>(source: &str, config: &crate::config::AppConfig, default: fn(&Path, &str) -> bool, mut log: L) -> Self
+    where
+        L: FnMut(String),
+    {
+        let pattern = config
+            .sources
+            .get(source)
+            .and_then(|s| s.pattern.as_deref())
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .and_then(|p| match glob::Pattern::new(p) {
+                Ok(pattern) => Some(pattern),
+                Err(err) => {
+                    log(format!("[watch][{}] invalid pattern {:?} ignored: {}", source, p, err));
+                    None
+                }
+            });
+        Self { pattern, default }
+    }
+
+    fn matches(&self, path: &Path, name: &str) -> bool {
+        match &self.pattern {
+            Some(pattern) => pattern.matches_with(
+                name,
+                glob::MatchOptions { case_sensitive: false, ..glob::MatchOptions::new() },
+            ),
+            None => (self.default)(path, name),
+        }
+    }
+}
+
 fn find_latest_file<F>(root_dir: &Path, mut is_candidate: F) -> Option<PathBuf>
 where
     F: FnMut(&Path, &str) -> bool,
@@ -283,6 +514,44 @@ fn unique_paths(paths: Vec<PathBuf>) -> Vec<PathBuf> {
     result
 }
 
+// 日志目录优先级：sources.<name>.log_dir > 环境变量（如 CLAUDE_CONFIG_DIR/CODEX_HOME）+ 子目录 > 主目录下默认子路径
+fn resolve_source_root(
+    home: &Path,
+    log_dir: Option<&str>,
+    env_dir: Option<(&str, &str)>,
+    default_subpath: &str,
+) -> PathBuf {
+    if let Some(dir) = log_dir.map(str::trim).filter(|d| !d.is_empty()) {
+        return expand_home_path(home, dir);
+    }
+
+    if let Some((var, subdir)) = env_dir {
+        if let Ok(value) = std::env::var(var) {
+            if !value.trim().is_empty() {
+                return expand_home_path(home, value.trim()).join(subdir);
+            }
+        }
+    }
+
+    home.join(default_subpath)
+}
+
+fn expand_home_path(home: &Path, dir: &str) -> PathBuf {
+    if dir == "~" {
+        return home.to_path_buf();
+    }
+    if let Some(rest) = dir.strip_prefix("~/").or_else(|| dir.strip_prefix("~\\")) {
+        return home.join(rest);
+    }
+
+    let path = PathBuf::from(dir);
+    if path.is_absolute() {
+        path
+    } else {
+        home.join(path)
+    }
+}
+
 fn get_opencode_data_dirs(home: &Path) -> Vec<PathBuf> {
     let mut dirs = vec![home.join(".local/share/opencode")];
 
@@ -312,11 +581,11 @@ fn is_opencode_db_file(name: &str) -> bool {
     lower.starts_with("opencode") && lower.ends_with(".db")
 }
 
-fn find_latest_opencode_db(home: &Path) -> Option<PathBuf> {
+fn find_latest_opencode_db(dirs: &[PathBuf]) -> Option<PathBuf> {
     let mut latest: Option<(PathBuf, u128)> = None;
 
-    for dir in get_opencode_data_dirs(home) {
-        let Ok(entries) = fs::read_dir(&dir) else { continue; };
+    for dir in dirs {
+        let Ok(entries) = fs::read_dir(dir) else { continue; };
         for entry in entries.flatten() {
             let path = entry.path();
             if !path.is_file() {
@@ -388,13 +657,12 @@ fn extract_text_from_any(value: &Value) -> String {
     }
 }
 
-// 检测 Codex turn-end 确认提示
-fn detect_turn_end_confirm_prompt(text: &str) -> Option<String> {
+// 取文本末尾最多 6 行（限制 1200 字符），作为确认检测的范围
+fn extract_confirm_tail(text: &str) -> Option<String> {
     if text.trim().is_empty() {
         return None;
     }
 
-    // 取最后 6 行，最多 1200 字符
     let raw = text.replace("\r\n", "\n");
     let limited = if raw.len() > 1200 {
         let byte_offset = raw.len() - 1200;
@@ -411,10 +679,18 @@ fn detect_turn_end_confirm_prompt(text: &str) -> Option<String> {
         .collect();
 
     let tail: Vec<&str> = lines.into_iter().rev().take(6).rev().collect();
-    let tail_text = tail.join("\n");
+    if tail.is_empty() {
+        return None;
+    }
+    Some(tail.join("\n"))
+}
+
+// 检测 Codex turn-end 确认提示
+fn detect_turn_end_confirm_prompt(text: &str) -> Option<String> {
+    let tail_text = extract_confirm_tail(text)?;
     let tail_lower = tail_text.to_lowercase();
 
-    let last_line = tail.last().unwrap_or(&"");
+    let last_line = tail_text.lines().last().unwrap_or("");
     let ends_with_question = last_line.ends_with('?') || last_line.ends_with('？');
 
     // 检查确认提示词
@@ -443,6 +719,129 @@ fn detect_turn_end_confirm_prompt(text: &str) -> Option<String> {
     None
 }
 
+// settings.json 变更检测：mtime 变化后需保持稳定一段时间才触发重载，合并连续写入
+const CONFIG_RELOAD_DEBOUNCE_MS: i64 = 1000;
+
+struct ConfigFileWatcher {
+    loaded_mtime: Option<SystemTime>,
+    pending: Option<(Option<SystemTime>, i64)>,
+}
+
+impl ConfigFileWatcher {
+    fn new(path: &Path) -> Self {
+        Self {
+            loaded_mtime: safe_stat(path).and_then(|m| m.modified().ok()),
+            pending: None,
+        }
+    }
+
+    fn poll(&mut self, path: &Path) -> bool {
+        let mtime = safe_stat(path).and_then(|m| m.modified().ok());
+        self.poll_at(mtime, now_unix_millis_i64())
+    }
+
+    fn poll_at(&mut self, mtime: Option<SystemTime>, now: i64) -> bool {
+        if mtime == self.loaded_mtime {
+            self.pending = None;
+            return false;
+        }
+
+        match self.pending {
+            Some((pending_mtime, since)) if pending_mtime == mtime => {
+                if now - since < CONFIG_RELOAD_DEBOUNCE_MS {
+                    return false;
+                }
+                self.loaded_mtime = mtime;
+                self.pending = None;
+                true
+            }
+            _ => {
+                self.pending = Some((mtime, now));
+                false
+            }
+        }
+    }
+}
+
+// 确认提示检测器：配置了正则时使用正则，否则回退到内置关键词匹配
+struct ConfirmDetector {
+    enabled: bool,
+    regexes: Vec<regex::Regex>,
+    min_turn_ms: i64,
+    require_question: bool,
+}
+
+impl ConfirmDetector {
+    fn new<L>(config: &crate::config::ConfirmAlertConfig, mut log: L) -> Self
+    where
+        L: FnMut(String),
+    {
+        let mut regexes = Vec::new();
+        for pattern in &config.confirm_regexes {
+            if pattern.trim().is_empty() {
+                continue;
+            }
+            match regex::Regex::new(pattern) {
+                Ok(re) => regexes.push(re),
+                Err(err) => log(format!("[watch][confirm] invalid regex {:?} skipped: {}", pattern, err)),
+            }
+        }
+
+        Self {
+            enabled: config.enabled || is_confirm_alert_enabled(),
+            regexes,
+            min_turn_ms: config.confirm_min_duration_seconds.max(0) as i64 * 1000,
+            require_question: config.confirm_require_question,
+        }
+    }
+
+    // 轮次开始时间未知时不做限制
+    fn allows_turn(&self, turn_started_at: Option<i64>, at: i64) -> bool {
+        self.min_turn_ms <= 0
+            || turn_started_at.map(|start| at - start >= self.min_turn_ms).unwrap_or(true)
+    }
+
+    fn detect(&self, text: &str) -> Option<String> {
+        let prompt = if self.regexes.is_empty() {
+            detect_turn_end_confirm_prompt(text)?
+        } else {
+            let tail_text = extract_confirm_tail(text)?;
+            if !self.regexes.iter().any(|re| re.is_match(&tail_text)) {
+                return None;
+            }
+            tail_text
+        };
+
+        if self.require_question && !is_question_prompt(&prompt) {
+            return None;
+        }
+        Some(prompt)
+    }
+}
+
+// 最后一行以问号结尾，或带有选项列表
+fn is_question_prompt(prompt: &str) -> bool {
+    let last_line = prompt.lines().rev().map(str::trim).find(|line| !line.is_empty()).unwrap_or("");
+    last_line.ends_with('?') || last_line.ends_with('？') || has_options_in_prompt(prompt)
+}
+
+// 供设置界面试验确认规则：按当前配置构建检测器，不受 confirm_alert.enabled 影响；
+// 无效的正则与监听时一样跳过，并作为错误返回方便定位
+pub fn test_confirm_detect(text: &str) -> Result<Option<String>, String> {
+    let config = crate::config::load_config().map_err(|e| e.to_string())?;
+    let mut invalid = Vec::new();
+    let detector = ConfirmDetector::new(&config.confirm_alert, |msg| invalid.push(msg));
+    if !invalid.is_empty() {
+        return Err(invalid.join("\n"));
+    }
+    Ok(detector.detect(text))
+}
+
+// Codex 使用的内置轮次结束确认检测
+pub fn test_turn_end_confirm(text: &str) -> Option<String> {
+    detect_turn_end_confirm_prompt(text)
+}
+
 // 检查是否有选项
 fn has_options_in_prompt(text: &str) -> bool {
     text.lines().any(|line| {
@@ -453,13 +852,105 @@ fn has_options_in_prompt(text: &str) -> bool {
     })
 }
 
+// 本轮第一条助手回复，是否通知由 notify_mode 决定
+fn spawn_first_reply_notification(
+    source: &'static str,
+    display_name: &'static str,
+    summary: Option<String>,
+    user_at: Option<i64>,
+    reply_at: Option<i64>,
+    cwd: String,
+) {
+    let duration_ms = user_at.zip(reply_at).map(|(start, end)| end - start).filter(|ms| *ms >= 0);
+    tauri::async_runtime::spawn(async move {
+        let _ = crate::notify::send_first_reply_notification(source, display_name, summary, duration_ms, cwd).await;
+    });
+}
+
+// 开启 sources.<name>.require_tool_use 时，本轮没有调用工具的完成通知不发送
+fn completion_allowed_for_tool_use(source: &str, turn_had_tool_use: bool) -> bool {
+    turn_had_tool_use
+        || !crate::config::load_config()
+            .ok()
+            .and_then(|config| config.sources.get(source).map(|s| s.require_tool_use))
+            .unwrap_or(false)
+}
+
+// 最后一条消息只是“好的”“Done.”之类的简短应答时不发完成通知；
+// 每项先按原文比较，再作为正则匹配整段文本，无效的正则只按原文比较
+fn is_ignored_completion(patterns: &[String], content: &str) -> bool {
+    let content = content.trim();
+    if content.is_empty() {
+        return false;
+    }
+    patterns.iter().filter(|p| !p.trim().is_empty()).any(|pattern| {
+        pattern.trim() == content
+            || regex::Regex::new(&format!("^(?:{})$", pattern)).is_ok_and(|re| re.is_match(content))
+    })
+}
+
+fn completion_allowed_for_content(summary: Option<&str>) -> bool {
+    let Some(summary) = summary else {
+        return true;
+    };
+    !crate::config::load_config()
+        .map(|config| is_ignored_completion(&config.completion_ignore_patterns, summary))
+        .unwrap_or(false)
+}
+
+// 轮询间隔的上下限：过短会明显增加 CPU 占用，过长则通知延迟过大
+const WATCH_INTERVAL_MIN_MS: i32 = 100;
+const WATCH_INTERVAL_MAX_MS: i32 = 10_000;
+
+fn clamp_watch_interval_ms(interval_ms: i32) -> u64 {
+    interval_ms.clamp(WATCH_INTERVAL_MIN_MS, WATCH_INTERVAL_MAX_MS) as u64
+}
+
+// 同一确认提示在该时间窗口内只提醒一次
+const CONFIRM_DEDUPE_MS: i64 = 60_000;
+
+// 忽略大小写与空白差异
+fn normalize_confirm_text(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+// 判断是否应发送确认提醒；发送时记录本次的 key 与时间
+fn should_send_confirm(
+    last_confirm_key: &mut Option<String>,
+    last_confirm_at: &mut Option<i64>,
+    prompt: &str,
+    at: i64,
+) -> bool {
+    let key = normalize_confirm_text(prompt);
+    let duplicate = last_confirm_key.as_deref() == Some(key.as_str())
+        && last_confirm_at.is_some_and(|last| (at - last).abs() < CONFIRM_DEDUPE_MS);
+    if duplicate {
+        return false;
+    }
+    *last_confirm_key = Some(key);
+    *last_confirm_at = Some(at);
+    true
+}
+
 // 标准化源配置
+// sources 参数决定监听范围，再按配置中的 sources.<name>.enabled 过滤
+fn enabled_sources(requested: &[&'static str], config: &crate::config::AppConfig) -> Vec<&'static str> {
+    requested
+        .iter()
+        .copied()
+        .filter(|source| config.sources.get(source).is_some_and(|s| s.enabled))
+        .collect()
+}
+
 fn normalize_sources(input: &str) -> Vec<&'static str> {
     let input = input.to_lowercase();
     let parts: Vec<&str> = input.split(',').map(|s| s.trim()).collect();
 
     if parts.contains(&"all") || parts.is_empty() {
-        vec!["claude", "codex", "gemini", "qwen", "opencode"]
+        vec!["claude", "codex", "gemini", "qwen", "opencode", "aider", "cursor"]
     } else {
         let mut result = Vec::new();
         for part in parts {
@@ -469,11 +960,13 @@ fn normalize_sources(input: &str) -> Vec<&'static str> {
                 "gemini" => result.push("gemini"),
                 "qwen" => result.push("qwen"),
                 "opencode" => result.push("opencode"),
+                "aider" => result.push("aider"),
+                "cursor" => result.push("cursor"),
                 _ => {}
             }
         }
         if result.is_empty() {
-            vec!["claude", "codex", "gemini", "qwen", "opencode"]
+            vec!["claude", "codex", "gemini", "qwen", "opencode", "aider", "cursor"]
         } else {
             result
         }
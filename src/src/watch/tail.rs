@@ -0,0 +1,171 @@
+// ============ 会话内容预览 ============
+
+// 在界面中展示正在跟随的会话文件的最近几轮对话，确认跟随的是正确的文件。
+// 支持 claude / codex / qwen（JSONL）与 gemini（会话 JSON）。
+const TRANSCRIPT_TEXT_MAX_CHARS: usize = 300;
+// 只读取文件末尾这么多字节，避免把很大的会话文件整体读入
+const TAIL_READ_BYTES: u64 = 2 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptEntry {
+    // user / assistant
+    pub role: String,
+    pub text: String,
+    pub at: Option<i64>,
+}
+
+// 跟随的文件读到新内容时发给前端的 session-tail 事件
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionTailEvent {
+    pub source: String,
+    pub path: String,
+    pub entries: Vec<TranscriptEntry>,
+}
+
+fn supports_transcript(source: &str) -> bool {
+    matches!(source, "claude" | "codex" | "qwen" | "gemini")
+}
+
+// 合并空白并截断，界面里每条只显示一段摘录
+fn transcript_text(text: &str) -> Option<String> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return None;
+    }
+    if text.chars().count() <= TRANSCRIPT_TEXT_MAX_CHARS {
+        return Some(text);
+    }
+    let mut truncated: String = text.chars().take(TRANSCRIPT_TEXT_MAX_CHARS).collect();
+    truncated.push('…');
+    Some(truncated)
+}
+
+fn transcript_entry(source: &str, obj: &Value) -> Option<TranscriptEntry> {
+    let kind = |value: &Value| value.get("type").and_then(|v| v.as_str()).map(str::to_string);
+    let (role, text) = match source {
+        "claude" | "qwen" => {
+            // 工具结果也以 user 记录写入；子代理的记录不属于主会话
+            if is_claude_tool_result_only(obj) || obj.get("isSidechain").and_then(|v| v.as_bool()) == Some(true) {
+                return None;
+            }
+            let role = match kind(obj)?.as_str() {
+                "user" => "user",
+                "assistant" => "assistant",
+                _ => return None,
+            };
+            let message = obj.get("message")?;
+            (role, extract_text_from_any(message.get("content").unwrap_or(message)))
+        }
+        // response_item 中混有系统指令，用 event_msg 里的用户消息与助手回复
+        "codex" => {
+            if kind(obj).as_deref() != Some("event_msg") {
+                return None;
+            }
+            let payload = obj.get("payload")?;
+            let role = match kind(payload)?.as_str() {
+                "user_message" => "user",
+                "agent_message" => "assistant",
+                _ => return None,
+            };
+            (role, payload.get("message").map(extract_text_from_any).unwrap_or_default())
+        }
+        "gemini" => {
+            let role = match kind(obj)?.as_str() {
+                "user" => "user",
+                "gemini" => "assistant",
+                _ => return None,
+            };
+            (role, obj.get("content").map(extract_text_from_any).unwrap_or_default())
+        }
+        _ => return None,
+    };
+
+    Some(TranscriptEntry {
+        role: role.to_string(),
+        text: transcript_text(&text)?,
+        at: obj.get("timestamp").and_then(parse_timestamp),
+    })
+}
+
+// 读取 [from, to) 之间完整的 JSONL 行；to 为 None 时读到文件末尾，未写完的最后一行忽略
+fn read_transcript_range(source: &str, path: &Path, from: u64, to: Option<u64>) -> std::io::Result<Vec<TranscriptEntry>> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(from))?;
+    let mut reader = BufReader::new(file).take(to.map(|to| to.saturating_sub(from)).unwrap_or(u64::MAX));
+    let mut entries = Vec::new();
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 || buf.last() != Some(&b'\n') {
+            break;
+        }
+        if let Some(entry) = safe_json_parse(&String::from_utf8_lossy(&buf)).and_then(|obj| transcript_entry(source, &obj)) {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+// 读取第 skip 条起的至多 limit 条消息
+fn read_gemini_transcript(path: &Path, skip: usize, limit: usize) -> Result<Vec<TranscriptEntry>, String> {
+    let content = read_gemini_session_file(path).map_err(|e| e.to_string())?;
+    let (messages, _) = collect_gemini_message_jsons(&content, skip).ok_or("会话文件不是有效的 Gemini 会话")?;
+    Ok(messages
+        .into_iter()
+        .take(limit)
+        .filter_map(|raw| serde_json::from_str::<Value>(raw).ok())
+        .filter_map(|msg| transcript_entry("gemini", &msg))
+        .collect())
+}
+
+// 返回会话文件最后 lines 条用户消息与助手回复
+pub fn tail_session_file(source: &str, path: &Path, lines: usize) -> Result<Vec<TranscriptEntry>, String> {
+    if !supports_transcript(source) {
+        return Err(format!("{} 暂不支持查看会话内容", source));
+    }
+
+    let mut entries = if source == "gemini" {
+        read_gemini_transcript(path, 0, usize::MAX)?
+    } else {
+        let size = fs::metadata(path).map_err(|e| e.to_string())?.len();
+        let mut from = size.saturating_sub(TAIL_READ_BYTES);
+        // 从文件中间开始读时跳过被截断的第一行
+        if from > 0 {
+            let mut file = File::open(path).map_err(|e| e.to_string())?;
+            file.seek(SeekFrom::Start(from)).map_err(|e| e.to_string())?;
+            let mut partial = Vec::new();
+            from += BufReader::new(file).read_until(b'\n', &mut partial).map_err(|e| e.to_string())? as u64;
+        }
+        read_transcript_range(source, path, from, None).map_err(|e| e.to_string())?
+    };
+
+    let skip = entries.len().saturating_sub(lines);
+    entries.drain(..skip);
+    Ok(entries)
+}
+
+// 对比上一轮与本轮快照，把跟随文件中新读到的对话以 session-tail 事件发给前端
+fn emit_session_tail(previous: &[SourceWatchDebug], next: &[SourceWatchDebug]) {
+    for source in next.iter().filter(|s| s.running && supports_transcript(&s.source)) {
+        let Some(before) = previous.iter().find(|s| s.source == source.source) else {
+            continue;
+        };
+        for file in &source.files {
+            let Some(old) = before.files.iter().find(|f| f.path == file.path) else {
+                continue;
+            };
+            let path = Path::new(&file.path);
+            let entries = match (old.processed_offset, file.processed_offset, old.processed_messages, file.processed_messages) {
+                (Some(from), Some(to), _, _) if to > from => read_transcript_range(&source.source, path, from, Some(to)).unwrap_or_default(),
+                (_, _, Some(from), Some(to)) if to > from => read_gemini_transcript(path, from, to - from).unwrap_or_default(),
+                _ => continue,
+            };
+            if !entries.is_empty() {
+                crate::notify::emit_to_frontend(
+                    "session-tail",
+                    SessionTailEvent { source: source.source.clone(), path: file.path.clone(), entries },
+                );
+            }
+        }
+    }
+}
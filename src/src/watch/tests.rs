@@ -5,14 +5,20 @@ mod tests {
     use std::collections::HashSet;
     use std::fs;
 
+    fn default_confirm_detector() -> ConfirmDetector {
+        ConfirmDetector::new(&crate::config::ConfirmAlertConfig::default(), |_| {})
+    }
+
     #[test]
     fn test_normalize_sources() {
-        assert_eq!(normalize_sources("all"), vec!["claude", "codex", "gemini", "qwen", "opencode"]);
-        assert_eq!(normalize_sources(""), vec!["claude", "codex", "gemini", "qwen", "opencode"]);
+        assert_eq!(normalize_sources("all"), vec!["claude", "codex", "gemini", "qwen", "opencode", "aider", "cursor"]);
+        assert_eq!(normalize_sources(""), vec!["claude", "codex", "gemini", "qwen", "opencode", "aider", "cursor"]);
         assert_eq!(normalize_sources("claude"), vec!["claude"]);
         assert_eq!(normalize_sources("claude,codex"), vec!["claude", "codex"]);
         assert_eq!(normalize_sources("qwen"), vec!["qwen"]);
         assert_eq!(normalize_sources("opencode"), vec!["opencode"]);
+        assert_eq!(normalize_sources("aider"), vec!["aider"]);
+        assert_eq!(normalize_sources("cursor"), vec!["cursor"]);
     }
 
     #[test]
@@ -516,6 +522,124 @@ mod tests {
         let _ = fs::remove_dir(&temp_dir);
     }
 
+    #[test]
+    fn test_read_text_lines_from_offset_keeps_partial_line_for_next_poll() {
+        let temp_dir = std::env::temp_dir().join(format!("aitify-watch-aider-{}", now_unix_millis_i64()));
+        fs::create_dir_all(&temp_dir).expect("temp dir should be created");
+        let file_path = temp_dir.join(AIDER_HISTORY_FILE);
+        fs::write(&file_path, "#### 修复登录\r\n已修").expect("history file should be written");
+
+        let mut lines = Vec::new();
+        let offset = read_text_lines_from_offset(&file_path, 0, |line| lines.push(line.to_string()))
+            .expect("text read should succeed");
+        assert_eq!(lines, vec!["#### 修复登录".to_string()]);
+
+        fs::write(&file_path, "#### 修复登录\r\n已修复\n").expect("history file should be rewritten");
+        lines.clear();
+        let offset = read_text_lines_from_offset(&file_path, offset, |line| lines.push(line.to_string()))
+            .expect("second text read should succeed");
+        assert_eq!(lines, vec!["已修复".to_string()]);
+        assert_eq!(offset, fs::metadata(&file_path).expect("metadata should exist").len());
+
+        let _ = fs::remove_file(&file_path);
+        let _ = fs::remove_dir(&temp_dir);
+    }
+
+    #[test]
+    fn test_process_aider_lines_tracks_turns() {
+        let mut state = AiderState::new();
+
+        process_aider_line("# aider chat started at 2026-01-01 10:00:00", 1_000, &mut state);
+        process_aider_line("> Added src/main.rs to the chat", 1_000, &mut state);
+        assert_eq!(state.last_user_at, None);
+
+        process_aider_line("#### 修复登录问题", 2_000, &mut state);
+        process_aider_line("#### 顺便补充测试", 2_500, &mut state);
+        process_aider_line("", 3_000, &mut state);
+        process_aider_line("登录问题已修复。", 5_000, &mut state);
+        process_aider_line("> Applied edit to src/main.rs", 6_000, &mut state);
+
+        assert_eq!(state.last_user_at, Some(2_000));
+        assert_eq!(state.last_assistant_at, Some(5_000));
+        assert_eq!(state.last_assistant_content.as_deref(), Some("登录问题已修复。"));
+
+        process_aider_line("#### 下一个任务", 9_000, &mut state);
+        assert_eq!(state.last_user_at, Some(9_000));
+        assert_eq!(state.last_assistant_at, None);
+        assert_eq!(state.last_assistant_content, None);
+    }
+
+    #[test]
+    fn test_process_cursor_records_tracks_user_and_assistant_turns() {
+        let mut state = CursorSessionState::new();
+
+        process_cursor_object(&serde_json::json!({
+            "type": "turn_context",
+            "timestamp": "2026-01-01T00:00:00Z",
+            "payload": { "cwd": "D:/Code/Aitify" }
+        }), &mut state);
+        process_cursor_object(&serde_json::json!({
+            "role": "user",
+            "timestamp": "2026-01-01T00:00:01Z",
+            "message": { "content": "修复登录" }
+        }), &mut state);
+        process_cursor_object(&serde_json::json!({
+            "type": "response_item",
+            "timestamp": "2026-01-01T00:00:31Z",
+            "payload": { "type": "message", "role": "assistant", "content": [{ "type": "output_text", "text": "已修复" }] }
+        }), &mut state);
+
+        assert_eq!(state.last_cwd.as_deref(), Some("D:/Code/Aitify"));
+        assert_eq!(state.last_assistant_content.as_deref(), Some("已修复"));
+        assert_eq!(
+            state.last_assistant_at.zip(state.last_user_at).map(|(end, start)| end - start),
+            Some(30_000)
+        );
+
+        process_cursor_object(&serde_json::json!({ "type": "user", "timestamp": "2026-01-01T00:01:00Z" }), &mut state);
+        assert_eq!(state.last_assistant_at, None);
+        assert_eq!(state.last_assistant_content, None);
+    }
+
+    #[test]
+    fn test_resolve_source_root_prefers_log_dir_then_default() {
+        let home = std::env::temp_dir().join("aitify-home");
+
+        assert_eq!(resolve_source_root(&home, None, None, CLAUDE_DIR), home.join(CLAUDE_DIR));
+        assert_eq!(resolve_source_root(&home, Some("  "), None, CLAUDE_DIR), home.join(CLAUDE_DIR));
+        assert_eq!(resolve_source_root(&home, Some("~/nas/claude"), None, CLAUDE_DIR), home.join("nas/claude"));
+        assert_eq!(resolve_source_root(&home, Some("nas/codex"), None, CODEX_DIR), home.join("nas/codex"));
+
+        let absolute = std::env::temp_dir().join("aitify-nas").join("gemini");
+        assert_eq!(
+            resolve_source_root(&home, absolute.to_str(), None, GEMINI_DIR),
+            absolute
+        );
+        assert_eq!(
+            resolve_source_root(&home, None, Some(("AITIFY_TEST_UNSET_SOURCE_DIR", "projects")), CLAUDE_DIR),
+            home.join(CLAUDE_DIR)
+        );
+    }
+
+    #[test]
+    fn test_watch_event_serializes_with_type_tag() {
+        let event = WatchEvent::Completion {
+            source: "codex".to_string(),
+            duration_ms: Some(1500),
+            summary: Some("已修复".to_string()),
+        };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({ "type": "completion", "source": "codex", "duration_ms": 1500, "summary": "已修复" })
+        );
+
+        let following = WatchEvent::following("claude", Path::new("session.jsonl"));
+        assert_eq!(
+            serde_json::to_value(&following).unwrap(),
+            serde_json::json!({ "type": "following", "source": "claude", "file": "session.jsonl" })
+        );
+    }
+
     #[test]
     fn test_normalize_processed_offset_resets_to_zero_when_file_shrinks() {
         assert_eq!(normalize_processed_offset(128, 64), 64);
@@ -572,7 +696,7 @@ mod tests {
         .to_string();
         let mut state = GeminiState::new();
 
-        let total_count = process_gemini_messages_from_content(&content, 2, &mut state, 3000)
+        let total_count = process_gemini_messages_from_content(&content, 2, &mut state, 3000, None)
             .expect("gemini messages should be processed");
 
         assert_eq!(total_count, 4);
@@ -580,6 +704,150 @@ mod tests {
         assert_eq!(state.last_gemini_at, Some(1704067380000));
     }
 
+    #[test]
+    fn test_candidate_filter_uses_pattern_or_default() {
+        let mut config = crate::config::AppConfig::default();
+        let path = Path::new("/logs/chats/session-1.json");
+
+        let filter = CandidateFilter::new("gemini", &config, is_gemini_session_file, |_| {});
+        assert!(filter.matches(path, "session-1.json"));
+        assert!(!filter.matches(Path::new("/logs/session-1.jsonl"), "session-1.jsonl"));
+
+        config.sources.gemini.pattern = Some("Session-*.JSONL".to_string());
+        let filter = CandidateFilter::new("gemini", &config, is_gemini_session_file, |_| {});
+        assert!(filter.matches(Path::new("/logs/session-1.jsonl"), "session-1.jsonl"));
+        assert!(!filter.matches(path, "session-1.json"));
+
+        // 无效的 pattern 记录日志后回退到默认规则
+        config.sources.gemini.pattern = Some("session-[".to_string());
+        let mut logs = Vec::new();
+        let filter = CandidateFilter::new("gemini", &config, is_gemini_session_file, |line| logs.push(line));
+        assert!(filter.matches(path, "session-1.json"));
+        assert_eq!(logs.len(), 1);
+    }
+
+    #[test]
+    fn test_stop_handle_signals_stop_on_drop() {
+        let running = Arc::new(AtomicBool::new(true));
+        drop(StopHandle::new(running.clone()));
+        assert!(!running.load(Ordering::Relaxed));
+
+        let running = Arc::new(AtomicBool::new(true));
+        StopHandle::new(running.clone()).stop();
+        assert!(!running.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_clamp_watch_interval_ms() {
+        assert_eq!(clamp_watch_interval_ms(0), 100);
+        assert_eq!(clamp_watch_interval_ms(250), 250);
+        assert_eq!(clamp_watch_interval_ms(60_000), 10_000);
+    }
+
+    #[test]
+    fn test_process_gemini_messages_refreshes_rewritten_last_message() {
+        let content = |text: &str| {
+            serde_json::json!({
+                "messages": [
+                    { "type": "user", "timestamp": "2024-01-01T00:00:00Z", "content": "hi" },
+                    { "type": "gemini", "timestamp": "2024-01-01T00:01:00Z", "content": text }
+                ]
+            })
+            .to_string()
+        };
+        let mut state = GeminiState::new();
+
+        let total_count = process_gemini_messages_from_content(&content("正在"), 0, &mut state, 3000, None)
+            .expect("gemini messages should be processed");
+        assert_eq!(state.last_gemini_content.as_deref(), Some("正在"));
+
+        // 条数不变，最后一条内容被改写
+        let total_count = process_gemini_messages_from_content(&content("正在生成最终回复"), total_count, &mut state, 3000, None)
+            .expect("gemini messages should be processed");
+        assert_eq!(total_count, 2);
+        assert_eq!(state.last_gemini_content.as_deref(), Some("正在生成最终回复"));
+        assert_eq!(state.last_user_at, Some(1704067200000));
+    }
+
+    #[test]
+    fn test_confirm_require_question_filters_statements() {
+        let mut config = crate::config::ConfirmAlertConfig { enabled: true, ..Default::default() };
+        let statement = "已按你的要求修改，你想再看看的话告诉我。";
+        assert!(ConfirmDetector::new(&config, |_| {}).detect(statement).is_some());
+
+        config.confirm_require_question = true;
+        let detector = ConfirmDetector::new(&config, |_| {});
+        assert!(detector.detect(statement).is_none());
+        assert!(detector.detect("需要修改配置文件，是否继续？").is_some());
+        assert!(detector.detect("请选择下一步：\n选项：\n1. 继续\n2. 停止").is_some());
+    }
+
+    #[test]
+    fn test_gemini_confirm_prompt_dedupes_streamed_updates() {
+        let config = crate::config::ConfirmAlertConfig { enabled: true, ..Default::default() };
+        let detector = ConfirmDetector::new(&config, |_| {});
+        let mut state = GeminiState::new();
+        state.last_user_at = Some(1704067200000);
+        state.last_gemini_content = Some("需要修改配置文件，是否继续？".to_string());
+
+        assert!(gemini_confirm_prompt(&mut state, &detector, 1704067210000).is_some());
+        assert!(state.confirm_notified_for_turn);
+        // 同一条消息的下一次流式更新不再提醒
+        assert!(gemini_confirm_prompt(&mut state, &detector, 1704067211000).is_none());
+
+        // 新的用户轮次清空去重记录
+        let user = serde_json::json!({ "type": "user", "timestamp": "2024-01-01T00:01:00Z" });
+        process_gemini_message(&user, &mut state, 3000, Some(&detector));
+        state.last_gemini_content = Some("需要修改配置文件，是否继续？".to_string());
+        assert!(gemini_confirm_prompt(&mut state, &detector, 1704067270000).is_some());
+    }
+
+    #[test]
+    fn test_read_jsonl_objects_waits_for_split_utf8_line() {
+        use std::io::Write;
+
+        let dir = std::env::temp_dir().join(format!("aitify-jsonl-utf8-{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("temp dir should be created");
+        let path = dir.join("session.jsonl");
+
+        let line = "{\"text\":\"任务完成\"}\n".as_bytes().to_vec();
+        // 第一次写入截断在“成”字的第一个字节之后
+        let split = line.len() - 5;
+        fs::write(&path, &line[..split]).expect("partial line should be written");
+
+        let mut texts = Vec::new();
+        let offset = read_jsonl_objects_from_offset(&path, 0, |obj| texts.push(obj["text"].as_str().unwrap_or_default().to_string()))
+            .expect("partial read should not fail");
+        assert_eq!(offset, 0);
+        assert!(texts.is_empty());
+
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&line[split..]).unwrap();
+        let offset = read_jsonl_objects_from_offset(&path, offset, |obj| texts.push(obj["text"].as_str().unwrap_or_default().to_string()))
+            .expect("complete read should succeed");
+        assert_eq!(offset, line.len() as u64);
+        assert_eq!(texts, vec!["任务完成".to_string()]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_replay_delay_scales_and_caps_gaps() {
+        assert_eq!(replay_delay_ms(None, Some(1_000), 1.0), 0);
+        assert_eq!(replay_delay_ms(Some(1_000), Some(3_000), 2.0), 1_000);
+        assert_eq!(replay_delay_ms(Some(3_000), Some(1_000), 1.0), 0);
+        assert_eq!(replay_delay_ms(Some(0), Some(600_000), 1.0), REPLAY_MAX_GAP_MS as u64);
+    }
+
+    #[test]
+    fn test_replay_log_rejects_sources_without_timestamps() {
+        let path = Path::new(AIDER_HISTORY_FILE);
+        for source in ["aider", "gemini", "opencode"] {
+            let result = tauri::async_runtime::block_on(replay_log(source, path, 1.0));
+            assert!(result.unwrap_err().contains(source));
+        }
+    }
+
     #[test]
     fn test_parse_timestamp() {
         let ts_str = serde_json::json!("2024-01-01T00:00:00Z");
@@ -592,6 +860,50 @@ mod tests {
         assert_eq!(parse_timestamp(&ts_num_ms), Some(1704067200000));
     }
 
+    #[test]
+    fn test_parse_timestamp_loose_formats() {
+        use chrono::TimeZone;
+
+        let local = chrono::Local
+            .with_ymd_and_hms(2024, 1, 1, 0, 0, 0)
+            .earliest()
+            .unwrap()
+            .timestamp_millis();
+        assert_eq!(parse_timestamp(&serde_json::json!("2024-01-01 00:00:00.123")), Some(local + 123));
+        assert_eq!(parse_timestamp(&serde_json::json!("2024-01-01 00:00:00")), Some(local));
+
+        assert_eq!(parse_timestamp(&serde_json::json!("2024-01-01T08:00:00.123+08:00")), Some(1704067200123));
+        assert_eq!(parse_timestamp(&serde_json::json!("2024-01-01 08:00:00+08:00")), Some(1704067200000));
+        assert_eq!(parse_timestamp(&serde_json::json!("Mon, 01 Jan 2024 08:00:00 +0800")), Some(1704067200000));
+    }
+
+    #[test]
+    fn test_claude_work_after_detects_tool_use_during_quiet_window() {
+        use std::io::Write;
+
+        let dir = std::env::temp_dir().join(format!("aitify-claude-recheck-{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("temp dir should be created");
+        let path = dir.join("session.jsonl");
+        let assistant_text = r#"{"type":"assistant","timestamp":"2024-01-01T00:00:10Z","message":{"content":[{"type":"text","text":"我先看看代码"}]}}"#;
+        fs::write(&path, format!("{}\n", assistant_text)).expect("session should be written");
+        let offset = fs::metadata(&path).unwrap().len();
+        let assistant_at = parse_timestamp(&serde_json::json!("2024-01-01T00:00:10Z")).unwrap();
+
+        // 计时期间没有新记录：照常通知
+        assert_eq!(claude_work_after(&path, offset, assistant_at, false), None);
+
+        // 计时期间写入了工具调用：应重新计时
+        let tool_use = r#"{"type":"assistant","timestamp":"2024-01-01T00:00:12Z","message":{"content":[{"type":"tool_use","name":"Read","input":{}}]}}"#;
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(file, "{}", tool_use).unwrap();
+        let next_offset = claude_work_after(&path, offset, assistant_at, false).expect("tool use should be detected");
+        assert_eq!(next_offset, fs::metadata(&path).unwrap().len());
+        // 子代理的计时器不受主会话记录影响
+        assert_eq!(claude_work_after(&path, offset, assistant_at, true), None);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn test_is_claude_work_type() {
         assert!(is_claude_work_type("progress"));
@@ -600,6 +912,164 @@ mod tests {
         assert!(!is_claude_work_type("assistant"));
     }
 
+    #[test]
+    fn test_process_claude_accumulates_turn_usage() {
+        let mut state = ClaudeState::new();
+        let assistant = |id: &str, output_tokens: i64| serde_json::json!({
+            "type": "assistant",
+            "timestamp": "2026-01-01T00:00:05Z",
+            "message": {
+                "id": id,
+                "role": "assistant",
+                "model": "claude-sonnet-4-5",
+                "content": [{ "type": "text", "text": "ok" }],
+                "usage": { "input_tokens": 10, "cache_read_input_tokens": 1000, "output_tokens": output_tokens }
+            }
+        });
+
+        process_claude_object(&serde_json::json!({
+            "type": "user",
+            "message": { "role": "user", "content": "修复登录" }
+        }), false, &mut state);
+        // 同一条消息的多条记录只计最新一次
+        process_claude_object(&assistant("msg_1", 5), false, &mut state);
+        process_claude_object(&assistant("msg_1", 50), false, &mut state);
+        process_claude_object(&serde_json::json!({
+            "type": "user",
+            "message": { "role": "user", "content": [{ "type": "tool_result", "content": "done" }] }
+        }), false, &mut state);
+        process_claude_object(&assistant("msg_2", 20), false, &mut state);
+
+        let usage = state.turn_token_usage().unwrap();
+        assert_eq!((usage.input_tokens, usage.cached_input_tokens, usage.output_tokens), (20, 2000, 70));
+        assert_eq!(usage.model.as_deref(), Some("claude-sonnet-4-5"));
+
+        process_claude_object(&serde_json::json!({
+            "type": "user",
+            "message": { "role": "user", "content": "继续" }
+        }), false, &mut state);
+        assert_eq!(state.turn_token_usage(), None);
+    }
+
+    #[test]
+    fn test_turn_tool_use_tracking_for_require_tool_use() {
+        let mut state = ClaudeState::new();
+        let user = |content: serde_json::Value| serde_json::json!({ "type": "user", "message": { "role": "user", "content": content } });
+        let assistant = |content: serde_json::Value| serde_json::json!({ "type": "assistant", "message": { "role": "assistant", "content": content } });
+
+        process_claude_object(&user(serde_json::json!("运行测试")), false, &mut state);
+        process_claude_object(&assistant(serde_json::json!([{ "type": "tool_use", "name": "Bash" }])), false, &mut state);
+        process_claude_object(&user(serde_json::json!([{ "type": "tool_result", "content": "ok" }])), false, &mut state);
+        process_claude_object(&assistant(serde_json::json!([{ "type": "text", "text": "测试通过" }])), false, &mut state);
+        // 最后一条回复没有工具调用，但本轮调用过
+        assert!(!state.last_assistant_had_tool_use);
+        assert!(state.turn_had_tool_use);
+
+        process_claude_object(&user(serde_json::json!("这是什么意思？")), false, &mut state);
+        process_claude_object(&assistant(serde_json::json!([{ "type": "text", "text": "解释" }])), false, &mut state);
+        assert!(!state.turn_had_tool_use);
+
+        assert!(gemini_message_has_tool_call(&serde_json::json!({ "type": "gemini", "toolCalls": [{ "name": "run_shell_command" }] })));
+        assert!(!gemini_message_has_tool_call(&serde_json::json!({ "type": "gemini", "content": "hello", "toolCalls": [] })));
+    }
+
+    #[test]
+    fn test_claude_sidechain_records_use_separate_subagent_state() {
+        let mut state = ClaudeState::new();
+        let record = |kind: &str, ts: &str, sidechain: bool| serde_json::json!({
+            "type": kind,
+            "timestamp": ts,
+            "isSidechain": sidechain,
+            "message": { "role": kind, "content": [{ "type": "text", "text": "子任务完成" }] }
+        });
+        let process = |state: &mut ClaudeState, obj: serde_json::Value, notify_subagents: bool| {
+            if let Some(target) = state.state_for_record(&obj, notify_subagents) {
+                process_claude_object(&obj, false, target);
+            }
+        };
+
+        process(&mut state, record("user", "2026-01-01T00:00:00Z", false), true);
+        // 默认不处理子代理记录
+        process(&mut state, record("assistant", "2026-01-01T00:00:30Z", true), false);
+        assert!(state.subagent.is_none());
+
+        process(&mut state, record("user", "2026-01-01T00:01:00Z", true), true);
+        process(&mut state, record("assistant", "2026-01-01T00:02:00Z", true), true);
+        let subagent = state.subagent.as_ref().expect("subagent state should be created");
+        assert!(subagent.is_subagent);
+        assert!(subagent.last_assistant_at > subagent.last_user_at);
+        // 主会话的轮次跟踪不受影响
+        assert_eq!(state.last_user_at, Some(1767225600000));
+        assert_eq!(state.last_assistant_at, None);
+    }
+
+    #[test]
+    fn test_process_claude_tracks_last_assistant_text() {
+        let mut state = ClaudeState::new();
+
+        process_claude_object(&serde_json::json!({
+            "type": "user",
+            "timestamp": "2026-01-01T00:00:00Z",
+            "message": { "role": "user", "content": "修复登录" }
+        }), false, &mut state);
+        process_claude_object(&serde_json::json!({
+            "type": "assistant",
+            "timestamp": "2026-01-01T00:00:05Z",
+            "message": { "role": "assistant", "content": [
+                { "type": "text", "text": "登录问题已修复" },
+                { "type": "tool_use", "name": "Bash", "input": { "command": "cargo test" } }
+            ] }
+        }), false, &mut state);
+        process_claude_object(&serde_json::json!({
+            "type": "assistant",
+            "timestamp": "2026-01-01T00:00:06Z",
+            "message": { "role": "assistant", "content": [
+                { "type": "tool_use", "name": "Bash", "input": { "command": "ls" } }
+            ] }
+        }), false, &mut state);
+
+        assert_eq!(state.last_assistant_content.as_deref(), Some("登录问题已修复"));
+
+        process_claude_object(&serde_json::json!({
+            "type": "user",
+            "timestamp": "2026-01-01T00:01:00Z",
+            "message": { "role": "user", "content": "继续" }
+        }), false, &mut state);
+        assert_eq!(state.last_assistant_content, None);
+    }
+
+    #[test]
+    fn test_extract_claude_error_detects_api_errors_and_failed_tool_results() {
+        let api_error = serde_json::json!({
+            "type": "assistant",
+            "isApiErrorMessage": true,
+            "message": { "role": "assistant", "content": [{ "type": "text", "text": "API Error: 529 Overloaded" }] }
+        });
+        assert_eq!(extract_claude_error(&api_error).as_deref(), Some("API Error: 529 Overloaded"));
+
+        let tool_error = serde_json::json!({
+            "type": "user",
+            "message": { "role": "user", "content": [
+                { "type": "tool_result", "tool_use_id": "t1", "is_error": true, "content": "command not found" }
+            ] }
+        });
+        assert_eq!(extract_claude_error(&tool_error).as_deref(), Some("command not found"));
+
+        let normal = serde_json::json!({
+            "type": "assistant",
+            "message": { "role": "assistant", "content": [{ "type": "text", "text": "完成" }] }
+        });
+        assert_eq!(extract_claude_error(&normal), None);
+
+        let mut state = ClaudeState::new();
+        process_claude_object(&tool_error, false, &mut state);
+        assert!(state.last_error.is_some());
+        process_claude_object(&normal, false, &mut state);
+        assert_eq!(state.last_error, None);
+        process_claude_object(&api_error, false, &mut state);
+        assert_eq!(state.last_error.as_deref(), Some("API Error: 529 Overloaded"));
+    }
+
     #[test]
     fn test_is_codex_work_type() {
         assert!(is_codex_work_type("function_call"));
@@ -608,6 +1078,72 @@ mod tests {
         assert!(!is_codex_work_type("task_complete"));
     }
 
+    #[test]
+    fn test_codex_header_cache_skips_stray_jsonl() {
+        let temp_dir = std::env::temp_dir().join(format!("aitify-codex-header-{}", now_unix_millis_i64()));
+        fs::create_dir_all(&temp_dir).expect("temp dir should be created");
+        let rollout = temp_dir.join("rollout-2025-01-01T00-00-00-abc.jsonl");
+        let legacy = temp_dir.join("legacy.jsonl");
+        let stray = temp_dir.join("notes.jsonl");
+        let partial = temp_dir.join("rollout-new.jsonl");
+        fs::write(&rollout, "{\"type\":\"session_meta\",\"payload\":{\"id\":\"abc\"}}\n").unwrap();
+        fs::write(&legacy, "{\"id\":\"abc\",\"timestamp\":\"2025-01-01T00:00:00Z\"}\n").unwrap();
+        fs::write(&stray, "{\"level\":\"info\",\"msg\":\"hi\"}\n").unwrap();
+        fs::write(&partial, "{\"type\":\"session_").unwrap();
+
+        let mut cache = CodexHeaderCache::default();
+        assert!(cache.is_session_file(&rollout));
+        assert!(cache.is_session_file(&legacy));
+        assert!(!cache.is_session_file(&stray));
+        // 首行没写完时不缓存，写完后重新检查
+        assert!(!cache.is_session_file(&partial));
+        fs::write(&partial, "{\"type\":\"session_meta\",\"payload\":{}}\n").unwrap();
+        assert!(cache.is_session_file(&partial));
+        // 已缓存的结果不再读取文件
+        fs::write(&stray, "{\"type\":\"session_meta\"}\n").unwrap();
+        assert!(!cache.is_session_file(&stray));
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_tail_session_file_returns_last_turns() {
+        let temp_dir = std::env::temp_dir().join(format!("aitify-tail-{}", now_unix_millis_i64()));
+        fs::create_dir_all(&temp_dir).expect("temp dir should be created");
+        let path = temp_dir.join("session.jsonl");
+        let lines = [
+            r#"{"type":"user","timestamp":"2025-01-01T00:00:00Z","message":{"role":"user","content":"第一个问题"}}"#,
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"第一个回答"}]}}"#,
+            r#"{"type":"user","message":{"content":[{"type":"tool_result","content":"ok"}]}}"#,
+            r#"{"type":"user","message":{"content":"第二个问题"}}"#,
+            r#"{"type":"assistant","isSidechain":true,"message":{"content":"子代理"}}"#,
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"第二个\n\n回答"}]}}"#,
+        ];
+        fs::write(&path, format!("{}\n{{\"type\":\"user\"", lines.join("\n"))).unwrap();
+
+        let entries = tail_session_file("claude", &path, 3).unwrap();
+        let texts: Vec<(&str, &str)> = entries.iter().map(|e| (e.role.as_str(), e.text.as_str())).collect();
+        assert_eq!(texts, vec![("assistant", "第一个回答"), ("user", "第二个问题"), ("assistant", "第二个 回答")]);
+
+        let all = tail_session_file("claude", &path, 10).unwrap();
+        assert_eq!(all.len(), 4);
+        assert!(all[0].at.is_some());
+        assert!(tail_session_file("aider", &path, 10).is_err());
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_transcript_entry_for_codex_uses_event_messages() {
+        let user = serde_json::json!({"type": "event_msg", "payload": {"type": "user_message", "message": "修复测试"}});
+        let reply = serde_json::json!({"type": "event_msg", "payload": {"type": "agent_message", "message": "已修复"}});
+        let instructions = serde_json::json!({"type": "response_item", "payload": {"type": "message", "role": "developer", "content": "system"}});
+
+        assert_eq!(transcript_entry("codex", &user).map(|e| e.role), Some("user".to_string()));
+        assert_eq!(transcript_entry("codex", &reply).map(|e| e.text), Some("已修复".to_string()));
+        assert!(transcript_entry("codex", &instructions).is_none());
+    }
+
     #[test]
     fn test_extract_text_from_any() {
         let text_only = serde_json::json!("Hello");
@@ -640,7 +1176,7 @@ mod tests {
             }
         });
 
-        process_codex_object(&meta, true, &mut state);
+        process_codex_object(&meta, true, &mut state, &default_confirm_detector(), 0);
 
         assert!(state.is_subagent_session);
         assert_eq!(state.last_cwd.as_deref(), Some("D:/Code/Aitify"));
@@ -658,7 +1194,7 @@ mod tests {
             }
         });
 
-        process_codex_object(&meta, true, &mut state);
+        process_codex_object(&meta, true, &mut state, &default_confirm_detector(), 0);
 
         assert!(!state.is_subagent_session);
         assert_eq!(state.last_cwd.as_deref(), Some("D:/Code/Aitify"));
@@ -681,12 +1217,218 @@ mod tests {
             }
         });
 
-        process_codex_object(&task_complete, false, &mut state);
+        process_codex_object(&task_complete, false, &mut state, &default_confirm_detector(), 0);
 
         assert_eq!(state.last_notified_turn_id, None);
         assert!(!state.confirm_notified_for_turn);
     }
 
+    #[test]
+    fn test_process_codex_task_complete_debounced_until_new_user_message() {
+        let mut state = CodexSessionState::new();
+        state.last_user_at = Some(1704067200000);
+
+        let task_complete = serde_json::json!({
+            "timestamp": "2024-01-01T00:02:00Z",
+            "type": "event_msg",
+            "payload": {
+                "type": "task_complete",
+                "turn_id": "turn-1",
+                "last_agent_message": "All tests pass."
+            }
+        });
+
+        process_codex_object(&task_complete, false, &mut state, &default_confirm_detector(), 60_000);
+
+        let cancel = state
+            .pending_completion
+            .as_ref()
+            .map(|(_, cancel)| cancel.clone())
+            .expect("completion should be pending during the quiet window");
+        assert!(!cancel.load(Ordering::Relaxed));
+        assert_eq!(state.last_notified_turn_id.as_deref(), Some("turn-1"));
+
+        let user_message = serde_json::json!({
+            "timestamp": "2024-01-01T00:02:01Z",
+            "type": "event_msg",
+            "payload": { "type": "user_message", "message": "one more thing" }
+        });
+        process_codex_object(&user_message, false, &mut state, &default_confirm_detector(), 60_000);
+
+        assert!(state.pending_completion.is_none());
+        assert!(cancel.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_process_codex_trailing_agent_message_reschedules_completion() {
+        let mut state = CodexSessionState::new();
+        state.last_user_at = Some(1704067200000);
+
+        let task_complete = serde_json::json!({
+            "timestamp": "2024-01-01T00:02:00Z",
+            "type": "event_msg",
+            "payload": { "type": "task_complete", "turn_id": "turn-1", "last_agent_message": "" }
+        });
+        process_codex_object(&task_complete, false, &mut state, &default_confirm_detector(), 0);
+
+        // quiet_ms 为 0 时也在宽限期内等待后续内容
+        let (completion_at, first_cancel) = state.pending_completion.clone().expect("completion should wait for the grace window");
+
+        let agent_message = serde_json::json!({
+            "timestamp": "2024-01-01T00:02:01Z",
+            "type": "event_msg",
+            "payload": { "type": "agent_message", "message": "Refactor finished, all tests pass." }
+        });
+        process_codex_object(&agent_message, false, &mut state, &default_confirm_detector(), 0);
+
+        assert!(first_cancel.load(Ordering::Relaxed));
+        assert_eq!(state.pending_completion.as_ref().map(|(at, _)| *at), Some(completion_at));
+        assert_eq!(state.last_agent_content.as_deref(), Some("Refactor finished, all tests pass."));
+        state.clear_pending_completion();
+    }
+
+    #[test]
+    fn test_process_codex_short_turn_skips_confirm_and_falls_back_to_completion() {
+        let config = crate::config::ConfirmAlertConfig {
+            confirm_min_duration_seconds: 30,
+            ..Default::default()
+        };
+        let detector = ConfirmDetector::new(&config, |_| {});
+        let mut state = CodexSessionState::new();
+        state.last_user_at = Some(1704067200000);
+
+        let task_complete = serde_json::json!({
+            "timestamp": "2024-01-01T00:00:10Z",
+            "type": "event_msg",
+            "payload": {
+                "type": "task_complete",
+                "turn_id": "turn-1",
+                "last_agent_message": "请确认是否继续执行？"
+            }
+        });
+
+        process_codex_object(&task_complete, false, &mut state, &detector, 60_000);
+
+        assert!(state.pending_completion.is_some());
+        assert!(state.confirm_notified_for_turn);
+        state.clear_pending_completion();
+    }
+
+    #[test]
+    fn test_process_codex_approval_request_confirms_once_per_turn() {
+        let detector = default_confirm_detector();
+        let mut state = CodexSessionState::new();
+        state.last_user_at = Some(1704067200000);
+
+        let approval = serde_json::json!({
+            "timestamp": "2024-01-01T00:00:05Z",
+            "type": "event_msg",
+            "payload": {
+                "type": "exec_approval_request",
+                "call_id": "call-1",
+                "command": ["git", "push", "origin", "main"],
+                "cwd": "/repo"
+            }
+        });
+        process_codex_object(&approval, false, &mut state, &detector, 0);
+        assert!(state.interaction_required_for_turn);
+        assert_eq!(state.last_confirm_key.as_deref(), Some(normalize_confirm_text("请求执行命令：git push origin main").as_str()));
+
+        // 未批准就结束的轮次不再重复提醒，也不发完成通知
+        let task_complete = serde_json::json!({
+            "timestamp": "2024-01-01T00:00:10Z",
+            "type": "event_msg",
+            "payload": { "type": "task_complete", "turn_id": "turn-1", "last_agent_message": "" }
+        });
+        process_codex_object(&task_complete, false, &mut state, &detector, 0);
+        assert!(state.pending_completion.is_none());
+        assert_eq!(state.last_notified_turn_id.as_deref(), Some("turn-1"));
+
+        let patch = serde_json::json!({
+            "type": "apply_patch_approval_request",
+            "changes": { "src/main.rs": {}, "README.md": {} }
+        });
+        let prompt = codex_approval_prompt("apply_patch_approval_request", patch.as_object().unwrap());
+        assert!(prompt.starts_with("请求修改文件："));
+        assert!(prompt.contains("src/main.rs") && prompt.contains("README.md"));
+    }
+
+    #[test]
+    fn test_process_codex_token_count_tracks_turn_usage() {
+        let mut state = CodexSessionState::new();
+        let token_count = |total: i64| serde_json::json!({
+            "timestamp": "2024-01-01T00:00:00Z",
+            "type": "event_msg",
+            "payload": { "type": "token_count", "info": { "total_token_usage": { "input_tokens": total - 100, "output_tokens": 100, "total_tokens": total } } }
+        });
+
+        process_codex_object(&serde_json::json!({
+            "type": "turn_context",
+            "payload": { "turn_id": "t1", "model": "gpt-5-codex" }
+        }), true, &mut state, &default_confirm_detector(), 0);
+        process_codex_object(&token_count(10_000), true, &mut state, &default_confirm_detector(), 0);
+        assert_eq!(state.turn_token_usage().map(|u| u.total_tokens()), Some(10_000));
+
+        process_codex_object(&serde_json::json!({
+            "timestamp": "2024-01-01T00:01:00Z",
+            "type": "event_msg",
+            "payload": { "type": "user_message", "message": "继续" }
+        }), false, &mut state, &default_confirm_detector(), 0);
+        process_codex_object(&token_count(52_000), false, &mut state, &default_confirm_detector(), 0);
+        let usage = state.turn_token_usage().unwrap();
+        assert_eq!(usage.total_tokens(), 42_000);
+        assert_eq!(usage.output_tokens, 0);
+        assert_eq!(usage.model.as_deref(), Some("gpt-5-codex"));
+
+        let flat = serde_json::json!({ "type": "token_count", "input_tokens": 1200, "cached_input_tokens": 200, "output_tokens": 300 });
+        let totals = extract_codex_token_totals(flat.as_object().unwrap()).unwrap();
+        assert_eq!((totals.input_tokens, totals.cached_input_tokens, totals.output_tokens), (1000, 200, 300));
+    }
+
+    #[test]
+    fn test_enabled_sources_filters_disabled_config() {
+        let mut config = crate::config::AppConfig::default();
+        config.sources.gemini.enabled = false;
+
+        let all = normalize_sources("all");
+        let active = enabled_sources(&all, &config);
+        assert!(!active.contains(&"gemini"));
+        // aider 默认关闭
+        assert!(!active.contains(&"aider"));
+        assert!(active.contains(&"claude"));
+
+        assert_eq!(enabled_sources(&normalize_sources("gemini,codex"), &config), vec!["codex"]);
+    }
+
+    #[test]
+    fn test_config_file_watcher_debounces_writes() {
+        let t = |secs| Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs));
+        let mut watcher = ConfigFileWatcher { loaded_mtime: t(1), pending: None };
+
+        assert!(!watcher.poll_at(t(1), 0));
+        assert!(!watcher.poll_at(t(2), 1000));
+        // 防抖期间再次写入，重新计时
+        assert!(!watcher.poll_at(t(3), 1500));
+        assert!(!watcher.poll_at(t(3), 2000));
+        assert!(watcher.poll_at(t(3), 2500));
+        assert!(!watcher.poll_at(t(3), 4000));
+    }
+
+    #[test]
+    fn test_is_ignored_completion_matches_exact_or_regex() {
+        let patterns = vec!["好的".to_string(), "(?i)done\\.?".to_string(), "[".to_string(), "  ".to_string()];
+        assert!(is_ignored_completion(&patterns, "  好的\n"));
+        assert!(is_ignored_completion(&patterns, "Done."));
+        assert!(is_ignored_completion(&patterns, "done"));
+        // 正则须匹配整段文本
+        assert!(!is_ignored_completion(&patterns, "Done. 已修改 3 个文件"));
+        assert!(!is_ignored_completion(&patterns, "好的，接下来运行测试"));
+        // 无效正则按原文比较
+        assert!(is_ignored_completion(&patterns, "["));
+        assert!(!is_ignored_completion(&patterns, ""));
+        assert!(!is_ignored_completion(&[], "好的"));
+    }
+
     #[test]
     fn test_detect_turn_end_confirm_prompt() {
         let text = "请确认是否继续执行？";
@@ -696,6 +1438,53 @@ mod tests {
         assert!(detect_turn_end_confirm_prompt(text).is_none());
     }
 
+    #[test]
+    fn test_confirm_detector_uses_regex_instead_of_keywords() {
+        let config = crate::config::ConfirmAlertConfig {
+            enabled: true,
+            confirm_regexes: vec![r"(?i)shall i (apply|run)\b.*\?$".to_string()],
+            ..Default::default()
+        };
+        let detector = ConfirmDetector::new(&config, |_| {});
+
+        assert_eq!(
+            detector.detect("Patch is ready.\nShall I apply it?"),
+            Some("Patch is ready.\nShall I apply it?".to_string())
+        );
+        // 关键词匹配会误报的句子，在正则模式下不应触发
+        assert!(detector.detect("I will confirm the build passes.").is_none());
+    }
+
+    #[test]
+    fn test_confirm_detector_allows_turn_respects_min_duration() {
+        let config = crate::config::ConfirmAlertConfig {
+            confirm_min_duration_seconds: 30,
+            ..Default::default()
+        };
+        let detector = ConfirmDetector::new(&config, |_| {});
+
+        assert!(!detector.allows_turn(Some(1_000), 20_000));
+        assert!(detector.allows_turn(Some(1_000), 31_000));
+        assert!(detector.allows_turn(None, 20_000));
+        assert!(default_confirm_detector().allows_turn(Some(1_000), 1_001));
+    }
+
+    #[test]
+    fn test_confirm_detector_skips_invalid_regex() {
+        let config = crate::config::ConfirmAlertConfig {
+            enabled: true,
+            confirm_regexes: vec!["(unclosed".to_string(), "是否继续".to_string()],
+            ..Default::default()
+        };
+        let mut logs = Vec::new();
+        let detector = ConfirmDetector::new(&config, |line| logs.push(line));
+
+        assert_eq!(detector.regexes.len(), 1);
+        assert_eq!(logs.len(), 1);
+        assert!(logs[0].contains("(unclosed"));
+        assert!(detector.detect("改动已完成，是否继续？").is_some());
+    }
+
     #[test]
     fn test_has_options_in_prompt() {
         let text = "选项：A / B / C";
@@ -708,6 +1497,21 @@ mod tests {
         assert!(!has_options_in_prompt(text));
     }
 
+    #[test]
+    fn test_confirm_dedupe_skips_repeated_prompt() {
+        let mut state = QwenSessionState::new();
+        let sends = [0, 5_000]
+            .iter()
+            .filter(|at| state.should_send_confirm("是否继续执行？", **at))
+            .count();
+        assert_eq!(sends, 1);
+
+        // 仅空白或大小写不同视为同一提示；不同提示或超出窗口后重新提醒
+        assert!(!state.should_send_confirm("  是否继续执行？ ", 10_000));
+        assert!(state.should_send_confirm("Proceed?", 11_000));
+        assert!(state.should_send_confirm("Proceed?", 11_000 + CONFIRM_DEDUPE_MS));
+    }
+
     #[test]
     fn test_process_qwen_records() {
         let mut state = QwenSessionState::new();
@@ -759,6 +1563,65 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_decode_gemini_session_bytes_handles_utf16_bom() {
+        let text = r#"{"messages":[]}"#;
+        let encode = |to_bytes: fn(u16) -> [u8; 2], bom: [u8; 2]| {
+            let mut bytes = bom.to_vec();
+            bytes.extend(text.encode_utf16().flat_map(to_bytes));
+            bytes
+        };
+
+        for bytes in [
+            encode(u16::to_le_bytes, [0xFF, 0xFE]),
+            encode(u16::to_be_bytes, [0xFE, 0xFF]),
+            [&[0xEF, 0xBB, 0xBF][..], text.as_bytes()].concat(),
+            text.as_bytes().to_vec(),
+        ] {
+            let decoded = decode_gemini_session_bytes(&bytes);
+            assert_eq!(decoded, text);
+            assert_eq!(collect_gemini_message_jsons(&decoded, 0).map(|(_, total)| total), Some(0));
+        }
+    }
+
+    #[test]
+    fn test_seed_catchup_ms_overrides_source_default() {
+        let mut source = crate::config::SourceConfig::default();
+        assert_eq!(seed_catchup_ms(&source, 30_000), 30_000);
+        source.seed_catchup_ms = Some(0);
+        assert_eq!(seed_catchup_ms(&source, 30_000), 0);
+        source.seed_catchup_ms = Some(5_000);
+        assert_eq!(seed_catchup_ms(&source, 30_000), 5_000);
+
+        assert!(seed_within_catchup(96_000, 100_000, 5_000));
+        assert!(!seed_within_catchup(94_000, 100_000, 5_000));
+        assert!(!seed_within_catchup(100_000, 100_000, 0));
+    }
+
+    #[test]
+    fn test_source_health_reports_stale_when_file_grows_unread() {
+        let file = |offset: u64, modified_at: i64| FollowedFileDebug {
+            path: "/tmp/session.jsonl".to_string(),
+            modified_at: Some(modified_at),
+            processed_offset: Some(offset),
+            processed_messages: None,
+        };
+        let first = SourceWatchDebug::next(None, "codex", true, vec![file(10, 1_000)], 1_000);
+        // 读取位置不变时沿用上一轮的活动时间
+        let second = SourceWatchDebug::next(Some(&first), "codex", true, vec![file(10, 5_000)], 120_000);
+        assert_eq!(second.last_activity_at, 1_000);
+        assert!(source_health(&second, 120_000, 60_000).stale);
+        assert!(!source_health(&second, 120_000, 0).stale);
+
+        // 文件在最近一次活动之后没有写入时只是空闲
+        let idle = SourceWatchDebug::next(Some(&first), "codex", true, vec![file(10, 500)], 120_000);
+        assert!(!source_health(&idle, 120_000, 60_000).stale);
+
+        let resumed = SourceWatchDebug::next(Some(&second), "codex", true, vec![file(20, 5_000)], 121_000);
+        assert_eq!(resumed.last_activity_at, 121_000);
+        assert!(!source_health(&resumed, 121_000, 60_000).stale);
+    }
+
     #[test]
     fn test_process_qwen_official_chatrecord_jsonl_sample() {
         let mut state = QwenSessionState::new();
@@ -0,0 +1,289 @@
+use chrono::{Local, NaiveDate};
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::config::get_data_dir;
+
+const WATCH_LOG_PREFIX: &str = "watch-";
+const WATCH_LOG_SUFFIX: &str = ".log";
+// tail_lines 最多读取文件末尾的字节数，避免把大文件整份读入
+const TAIL_MAX_BYTES: u64 = 256 * 1024;
+
+pub fn get_watch_log_dir() -> PathBuf {
+    get_data_dir().join("watch-logs")
+}
+
+pub fn get_watch_log_path(date: NaiveDate) -> PathBuf {
+    get_watch_log_dir().join(format!("{}{}{}", WATCH_LOG_PREFIX, date.format("%Y-%m-%d"), WATCH_LOG_SUFFIX))
+}
+
+// 监听日志落盘：文件保持打开并缓冲写入，由调用方按节拍 flush，跨天自动切换文件
+#[derive(Clone, Default)]
+pub struct WatchLogWriter {
+    inner: Arc<Mutex<Option<(NaiveDate, BufWriter<File>)>>>,
+}
+
+impl WatchLogWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn append(&self, line: &str) {
+        let now = Local::now();
+        let today = now.date_naive();
+        let mut guard = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+
+        if guard.as_ref().map(|(date, _)| *date != today).unwrap_or(true) {
+            if let Some((_, mut previous)) = guard.take() {
+                let _ = previous.flush();
+            }
+            let _ = fs::create_dir_all(get_watch_log_dir());
+            let Ok(file) = OpenOptions::new().create(true).append(true).open(get_watch_log_path(today)) else { return; };
+            *guard = Some((today, BufWriter::new(file)));
+        }
+
+        if let Some((_, writer)) = guard.as_mut() {
+            let _ = writeln!(writer, "[{}] {}", now.format("%H:%M:%S%.3f"), line);
+        }
+    }
+
+    pub fn flush(&self) {
+        let mut guard = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((date, writer)) = guard.as_mut() {
+            let _ = writer.flush();
+            // 日志被清空后丢弃旧句柄，下次写入时重新创建文件
+            if !get_watch_log_path(*date).exists() {
+                *guard = None;
+            }
+        }
+    }
+}
+
+// 发往前端的日志攒批发送：满 LOG_BATCH_MAX_LINES 行立即发出，其余由定时任务每 LOG_BATCH_INTERVAL_MS 发出，
+// 最后一个句柄释放（监听结束）时发出剩余的行。跟随大文件 seed 时可明显减少 IPC 次数
+const LOG_BATCH_MAX_LINES: usize = 50;
+const LOG_BATCH_INTERVAL_MS: u64 = 100;
+
+struct LogBatch {
+    lines: Vec<String>,
+    emit: Box<dyn Fn(Vec<String>) + Send>,
+}
+
+impl LogBatch {
+    fn flush(&mut self) {
+        if !self.lines.is_empty() {
+            (self.emit)(std::mem::take(&mut self.lines));
+        }
+    }
+}
+
+impl Drop for LogBatch {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+pub struct LogBatcher {
+    batch: Arc<Mutex<LogBatch>>,
+}
+
+impl LogBatcher {
+    pub fn new(emit: impl Fn(Vec<String>) + Send + 'static) -> Self {
+        let batcher = Self::unscheduled(emit);
+        let batch = Arc::downgrade(&batcher.batch);
+        tauri::async_runtime::spawn(async move {
+            loop {
+                tokio::time::sleep(std::time::Duration::from_millis(LOG_BATCH_INTERVAL_MS)).await;
+                let Some(batch) = batch.upgrade() else { break };
+                batch.lock().unwrap_or_else(|e| e.into_inner()).flush();
+            }
+        });
+        batcher
+    }
+
+    fn unscheduled(emit: impl Fn(Vec<String>) + Send + 'static) -> Self {
+        Self {
+            batch: Arc::new(Mutex::new(LogBatch { lines: Vec::new(), emit: Box::new(emit) })),
+        }
+    }
+
+    pub fn push(&self, line: String) {
+        let mut batch = self.batch.lock().unwrap_or_else(|e| e.into_inner());
+        batch.lines.push(line);
+        if batch.lines.len() >= LOG_BATCH_MAX_LINES {
+            batch.flush();
+        }
+    }
+}
+
+// 文件末尾最多 max_lines 行；从文件中间开始读时丢弃第一行（可能不完整）
+pub fn tail_lines(path: &Path, max_lines: usize) -> Vec<String> {
+    let Ok(mut file) = File::open(path) else { return Vec::new(); };
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let start = len.saturating_sub(TAIL_MAX_BYTES);
+    let mut buf = Vec::new();
+    if file.seek(SeekFrom::Start(start)).and_then(|_| file.read_to_end(&mut buf)).is_err() {
+        return Vec::new();
+    }
+
+    let text = String::from_utf8_lossy(&buf);
+    let lines: Vec<&str> = text.lines().skip(usize::from(start > 0)).collect();
+    lines[lines.len().saturating_sub(max_lines)..]
+        .iter()
+        .map(|line| line.to_string())
+        .collect()
+}
+
+// 从 watch-YYYY-MM-DD.log 文件名中解析日期
+fn parse_watch_log_date(name: &str) -> Option<NaiveDate> {
+    let date = name
+        .strip_prefix(WATCH_LOG_PREFIX)?
+        .strip_suffix(WATCH_LOG_SUFFIX)?;
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+// 目录下所有 watch-YYYY-MM-DD.log 文件及其日期
+fn list_watch_logs(dir: &Path) -> Vec<(PathBuf, NaiveDate)> {
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new(); };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter_map(|path| {
+            let date = path.file_name().and_then(|n| n.to_str()).and_then(parse_watch_log_date)?;
+            Some((path, date))
+        })
+        .collect()
+}
+
+// 已有日志的日期（YYYY-MM-DD），最新的在前
+pub fn list_log_dates() -> Vec<String> {
+    list_log_dates_in(&get_watch_log_dir())
+}
+
+fn list_log_dates_in(dir: &Path) -> Vec<String> {
+    let mut dates: Vec<NaiveDate> = list_watch_logs(dir).into_iter().map(|(_, date)| date).collect();
+    dates.sort_unstable_by(|a, b| b.cmp(a));
+    dates.into_iter().map(|date| date.format("%Y-%m-%d").to_string()).collect()
+}
+
+// 删除全部监听日志，返回 (已删除, 跳过)；Windows 上正在写入的文件删除失败时跳过
+pub fn clear_watch_logs() -> (usize, usize) {
+    clear_watch_logs_in(&get_watch_log_dir())
+}
+
+fn clear_watch_logs_in(dir: &Path) -> (usize, usize) {
+    let mut removed = 0;
+    let mut skipped = 0;
+    for (path, _) in list_watch_logs(dir) {
+        if fs::remove_file(&path).is_ok() {
+            removed += 1;
+        } else {
+            skipped += 1;
+        }
+    }
+    (removed, skipped)
+}
+
+pub fn cleanup_watch_logs(retention_days: i32) -> usize {
+    cleanup_watch_logs_in(&get_watch_log_dir(), retention_days, Local::now().date_naive())
+}
+
+fn cleanup_watch_logs_in(dir: &Path, retention_days: i32, today: NaiveDate) -> usize {
+    if retention_days <= 0 {
+        return 0;
+    }
+
+    let Some(cutoff) = today.checked_sub_days(chrono::Days::new(retention_days as u64)) else { return 0; };
+
+    list_watch_logs(dir)
+        .into_iter()
+        .filter(|(path, date)| *date < cutoff && fs::remove_file(path).is_ok())
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tail_lines_returns_last_lines() {
+        let dir = std::env::temp_dir().join(format!("aitify-tail-lines-{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("temp dir should be created");
+        let path = dir.join("session.jsonl");
+        fs::write(&path, "a\nb\nc\nd\n").expect("file should be written");
+
+        assert_eq!(tail_lines(&path, 2), vec!["c", "d"]);
+        assert_eq!(tail_lines(&path, 10), vec!["a", "b", "c", "d"]);
+        assert!(tail_lines(&dir.join("missing.jsonl"), 10).is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_cleanup_watch_logs_removes_only_expired_logs() {
+        let dir = std::env::temp_dir().join(format!("aitify-watch-logs-{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("temp dir should be created");
+        for name in ["watch-2024-05-01.log", "watch-2024-05-25.log", "watch-2024-06-01.log", "notes.txt", "watch-latest.log"] {
+            fs::write(dir.join(name), "x").expect("log file should be written");
+        }
+
+        let today = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        assert_eq!(cleanup_watch_logs_in(&dir, 0, today), 0);
+        assert_eq!(cleanup_watch_logs_in(&dir, 7, today), 1);
+
+        assert!(!dir.join("watch-2024-05-01.log").exists());
+        assert!(dir.join("watch-2024-05-25.log").exists());
+        assert!(dir.join("watch-2024-06-01.log").exists());
+        assert!(dir.join("notes.txt").exists());
+        assert!(dir.join("watch-latest.log").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_log_batcher_flushes_when_full_and_on_drop() {
+        let emitted = Arc::new(Mutex::new(Vec::new()));
+        let sink = emitted.clone();
+        let batcher = LogBatcher::unscheduled(move |lines| sink.lock().unwrap().push(lines.len()));
+
+        for i in 0..LOG_BATCH_MAX_LINES + 3 {
+            batcher.push(format!("line {}", i));
+        }
+        assert_eq!(*emitted.lock().unwrap(), vec![LOG_BATCH_MAX_LINES]);
+
+        drop(batcher);
+        assert_eq!(*emitted.lock().unwrap(), vec![LOG_BATCH_MAX_LINES, 3]);
+    }
+
+    #[test]
+    fn test_list_log_dates_sorts_newest_first() {
+        let dir = std::env::temp_dir().join(format!("aitify-watch-log-dates-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["watch-2024-06-01.log", "watch-2024-06-03.log", "watch-bad.log", "other.txt"] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+
+        assert_eq!(list_log_dates_in(&dir), vec!["2024-06-03", "2024-06-01"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_clear_watch_logs_keeps_directory_and_other_files() {
+        let dir = std::env::temp_dir().join(format!("aitify-watch-logs-clear-{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("temp dir should be created");
+        for name in ["watch-2024-05-01.log", "watch-2024-06-01.log", "notes.txt"] {
+            fs::write(dir.join(name), "x").expect("log file should be written");
+        }
+
+        assert_eq!(clear_watch_logs_in(&dir), (2, 0));
+        assert!(dir.is_dir());
+        assert!(dir.join("notes.txt").exists());
+        assert!(!dir.join("watch-2024-06-01.log").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
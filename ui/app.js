@@ -4,24 +4,47 @@ const WATCH_DEFAULTS = {
   sources: 'all',
   interval_ms: 1000,
   gemini_quiet_ms: 3000,
-  claude_quiet_ms: 3000
+  claude_quiet_ms: 3000,
+  codex_quiet_ms: 0
 };
 
 const state = {
   config: null,
-  watchRunning: false
+  watchRunning: false,
+  watchPaused: false,
+  configEditing: false,
+  snooze: { active: false, until: null, remaining_ms: 0 }
 };
 
+const SNOOZE_MINUTES = 30;
+
 async function init() {
   await loadConfig();
   setupEventListeners();
+  window.__TAURI__.event?.listen('config-reloaded', (event) => {
+    state.config = normalizeConfig(event.payload);
+    renderConfig();
+  });
+  window.__TAURI__.event?.listen('watch-status-changed', () => { void syncWatchStatus(); });
+  window.__TAURI__.event?.listen('snooze-changed', (event) => {
+    state.snooze = event.payload;
+    updateSnoozeStatus();
+  });
+  window.__TAURI__.event?.listen('watch-stale', (event) => {
+    const source = String(event.payload?.source || '').toUpperCase();
+    showToast(`${source} 的会话文件仍在更新，但监控已长时间未读到新内容，可尝试重启监控`, 'error');
+  });
   await syncWatchStatus();
+  await syncSnoozeStatus();
+  await detectSources();
 }
 
 function setupEventListeners() {
   document.getElementById('btn-toggle-watch')?.addEventListener('click', toggleWatch);
+  document.getElementById('btn-pause-watch')?.addEventListener('click', togglePauseWatch);
+  document.getElementById('btn-snooze')?.addEventListener('click', toggleSnooze);
 
-  ['claude', 'codex', 'gemini', 'qwen', 'opencode'].forEach(source => {
+  ['claude', 'codex', 'gemini', 'qwen', 'opencode', 'cursor', 'aider'].forEach(source => {
     document.getElementById(`source-${source}-enabled`)?.addEventListener('change', (e) => {
       updateSourceConfig(source, 'enabled', e.target.checked);
     });
@@ -31,9 +54,27 @@ function setupEventListeners() {
   });
 
   document.getElementById('btn-test-desktop')?.addEventListener('click', testNotification);
+  document.getElementById('btn-open-watch-log')?.addEventListener('click', openWatchLog);
+  document.getElementById('btn-clear-watch-logs')?.addEventListener('click', clearWatchLogs);
+  document.getElementById('btn-edit-config')?.addEventListener('click', editConfig);
+  document.getElementById('btn-capture-diagnostics')?.addEventListener('click', captureDiagnostics);
+  // 在外部编辑器中修改配置后，回到窗口时重新读取
+  window.addEventListener('focus', () => {
+    if (state.configEditing) reloadConfig();
+  });
   document.getElementById('setting-language')?.addEventListener('change', (e) => saveSetting('language', e.target.value));
   document.getElementById('setting-autostart')?.addEventListener('change', (e) => saveSetting('autostart', e.target.checked));
   document.getElementById('setting-silent-start')?.addEventListener('change', (e) => saveSetting('silent_start', e.target.checked));
+  document.getElementById('setting-watch-interval')?.addEventListener('blur', (e) => {
+    const value = clampWatchInterval(parseInt(e.target.value));
+    e.target.value = value;
+    saveSetting('watch_interval_ms', value);
+  });
+}
+
+function clampWatchInterval(value) {
+  if (!Number.isFinite(value)) return WATCH_DEFAULTS.interval_ms;
+  return Math.min(10000, Math.max(100, value));
 }
 
 async function loadConfig() {
@@ -49,9 +90,11 @@ async function syncWatchStatus() {
   try {
     const status = await invoke('watch_status');
     state.watchRunning = !!(status && status.running);
+    state.watchPaused = !!(status && status.paused);
   } catch (e) {
     console.error('Failed to sync watch status:', e);
     state.watchRunning = false;
+    state.watchPaused = false;
   } finally {
     updateWatchStatus();
   }
@@ -60,7 +103,7 @@ async function syncWatchStatus() {
 function renderConfig() {
   if (!state.config) return;
 
-  ['claude', 'codex', 'gemini', 'qwen', 'opencode'].forEach(source => {
+  ['claude', 'codex', 'gemini', 'qwen', 'opencode', 'cursor', 'aider'].forEach(source => {
     const cfg = state.config.sources[source];
     const enabledEl = document.getElementById(`source-${source}-enabled`);
     const durationEl = document.getElementById(`source-${source}-duration`);
@@ -74,22 +117,33 @@ function renderConfig() {
   if (langEl) langEl.value = state.config.ui.language || 'zh-CN';
   if (autostartEl) autostartEl.checked = state.config.ui.autostart || false;
   if (silentStartEl) silentStartEl.checked = state.config.ui.silent_start || false;
+  const watchIntervalEl = document.getElementById('setting-watch-interval');
+  if (watchIntervalEl) watchIntervalEl.value = state.config.ui.watch_interval_ms;
 }
 
 function updateSourceConfig(source, field, value) {
   if (!state.config) return;
   if (field === 'enabled') {
-    state.config.sources[source].enabled = value;
+    patchConfig(`sources.${source}.enabled`, value);
   } else if (field === 'minDurationMinutes') {
-    state.config.sources[source].min_duration_minutes = value;
+    patchConfig(`sources.${source}.min_duration_minutes`, value);
   }
-  saveConfig();
 }
 
 function saveSetting(field, value) {
   if (!state.config) return;
-  state.config.ui[field] = value;
-  saveConfig();
+  patchConfig(`ui.${field}`, value);
+}
+
+// 只写回改动的字段，不覆盖手动编辑或监听线程对 settings.json 的其他修改
+async function patchConfig(path, value) {
+  try {
+    state.config = normalizeConfig(await invoke('patch_config', { path, value }));
+    showToast('配置已保存', 'success');
+  } catch (e) {
+    showToast(typeof e === 'string' && e ? `保存失败：${e}` : '保存失败', 'error');
+    renderConfig();
+  }
 }
 
 function normalizeConfig(config) {
@@ -100,7 +154,7 @@ function normalizeConfig(config) {
   if (!next.channels.desktop) next.channels.desktop = { enabled: true };
 
   if (!next.sources) next.sources = {};
-  ['claude', 'codex', 'gemini', 'qwen', 'opencode'].forEach((source) => {
+  ['claude', 'codex', 'gemini', 'qwen', 'opencode', 'cursor', 'aider'].forEach((source) => {
     if (!next.sources[source]) next.sources[source] = {};
     if (typeof next.sources[source].enabled !== 'boolean') next.sources[source].enabled = true;
     if (typeof next.sources[source].min_duration_minutes !== 'number') next.sources[source].min_duration_minutes = 0;
@@ -111,28 +165,22 @@ function normalizeConfig(config) {
   if (!next.ui.language) next.ui.language = 'zh-CN';
   if (typeof next.ui.autostart !== 'boolean') next.ui.autostart = false;
   if (typeof next.ui.silent_start !== 'boolean') next.ui.silent_start = false;
+  if (typeof next.ui.watch_interval_ms !== 'number') next.ui.watch_interval_ms = WATCH_DEFAULTS.interval_ms;
 
   return next;
 }
 
-async function saveConfig() {
-  try {
-    await invoke('save_config', { config: state.config });
-    showToast('配置已保存', 'success');
-  } catch (e) {
-    showToast('保存失败', 'error');
-  }
-}
-
 async function toggleWatch() {
   try {
     if (state.watchRunning) {
       await invoke('stop_watch');
       state.watchRunning = false;
+      state.watchPaused = false;
       updateWatchStatus();
       showToast('监控已停止', 'info');
     } else {
-      await invoke('start_watch', { payload: WATCH_DEFAULTS });
+      const intervalMs = state.config ? state.config.ui.watch_interval_ms : WATCH_DEFAULTS.interval_ms;
+      await invoke('start_watch', { payload: { ...WATCH_DEFAULTS, interval_ms: intervalMs } });
       state.watchRunning = true;
       updateWatchStatus();
       showToast('监控已启动', 'success');
@@ -143,31 +191,166 @@ async function toggleWatch() {
   }
 }
 
+async function togglePauseWatch() {
+  try {
+    state.watchPaused = await invoke('set_watch_paused', { paused: !state.watchPaused });
+    updateWatchStatus();
+    showToast(state.watchPaused ? '监控已暂停' : '监控已恢复', 'info');
+  } catch (e) {
+    await syncWatchStatus();
+    showToast('操作失败', 'error');
+  }
+}
+
 function updateWatchStatus() {
   const watchIndicator = document.getElementById('watch-indicator');
   const watchStatusText = document.getElementById('watch-status-text');
   const btnToggleWatch = document.getElementById('btn-toggle-watch');
+  const btnPauseWatch = document.getElementById('btn-pause-watch');
+  const paused = state.watchRunning && state.watchPaused;
 
   if (watchIndicator) {
-    watchIndicator.className = state.watchRunning ? 'status-dot active' : 'status-dot';
+    watchIndicator.className = state.watchRunning && !paused ? 'status-dot active' : 'status-dot';
   }
   if (watchStatusText) {
-    watchStatusText.textContent = state.watchRunning ? '监控运行中' : '监控已停止';
+    watchStatusText.textContent = !state.watchRunning ? '监控已停止' : (paused ? '监控已暂停' : '监控运行中');
   }
   if (btnToggleWatch) {
     btnToggleWatch.textContent = state.watchRunning ? '停止' : '启动';
   }
+  if (btnPauseWatch) {
+    btnPauseWatch.hidden = !state.watchRunning;
+    btnPauseWatch.textContent = paused ? '恢复' : '暂停';
+  }
 }
 
+// 日志目录不存在的来源在名称旁标注"未检测到"
+async function detectSources() {
+  try {
+    const detections = await invoke('detect_sources');
+    detections.forEach(({ source, installed, log_dir: logDir }) => {
+      const label = document.getElementById(`source-${source}-enabled`)?.closest('.source-row')?.querySelector('.source-label');
+      if (!label) return;
+      let hint = label.querySelector('.source-detect');
+      if (!hint) {
+        hint = document.createElement('span');
+        hint.className = 'source-detect';
+        label.appendChild(hint);
+      }
+      hint.hidden = installed;
+      hint.textContent = '未检测到';
+      hint.title = logDir ? `未找到日志：${logDir}` : '';
+    });
+  } catch (e) {
+    console.error('Failed to detect sources:', e);
+  }
+}
+
+async function syncSnoozeStatus() {
+  try {
+    state.snooze = await invoke('get_snooze_status');
+  } catch (e) {
+    console.error('Failed to sync snooze status:', e);
+  } finally {
+    updateSnoozeStatus();
+  }
+}
+
+async function toggleSnooze() {
+  try {
+    state.snooze = state.snooze.active
+      ? await invoke('cancel_snooze')
+      : await invoke('snooze', { minutes: SNOOZE_MINUTES });
+    updateSnoozeStatus();
+    showToast(state.snooze.active ? `已静音 ${SNOOZE_MINUTES} 分钟` : '已取消静音', 'info');
+  } catch (e) {
+    showToast('操作失败', 'error');
+  }
+}
+
+let snoozeExpiryTimer = null;
+
+function updateSnoozeStatus() {
+  // 静音到期后自动刷新按钮
+  clearTimeout(snoozeExpiryTimer);
+  if (state.snooze.active) {
+    snoozeExpiryTimer = setTimeout(() => { void syncSnoozeStatus(); }, state.snooze.remaining_ms + 500);
+  }
+
+  const btnSnooze = document.getElementById('btn-snooze');
+  if (!btnSnooze) return;
+  if (state.snooze.active && state.snooze.until) {
+    const until = new Date(state.snooze.until).toLocaleTimeString([], { hour: '2-digit', minute: '2-digit' });
+    btnSnooze.textContent = `取消静音（至 ${until}）`;
+  } else {
+    btnSnooze.textContent = `静音 ${SNOOZE_MINUTES} 分钟`;
+  }
+}
+
+// 逐个通道测试，未配置的通道不算失败
 async function testNotification() {
   try {
-    await invoke('test_notification', { payload: { source: 'claude', task_info: '这是一条测试通知', duration_minutes: null } });
-    showToast('测试通知已发送', 'success');
+    const results = await invoke('test_all_channels', { source: 'claude' });
+    const failed = results.filter((r) => !r.ok && r.error !== 'not configured');
+    const summary = results
+      .map((r) => (r.ok ? `${r.channel} ✓ ${r.latency_ms}ms` : `${r.channel} ✗ ${r.error || ''}`))
+      .join('；');
+    showToast(summary || '没有可测试的通道', failed.length ? 'error' : 'success');
   } catch (e) {
     showToast('测试通知失败', 'error');
   }
 }
 
+async function openWatchLog() {
+  try {
+    await invoke('open_watch_log');
+  } catch (e) {
+    showToast('打开日志失败', 'error');
+  }
+}
+
+async function editConfig() {
+  try {
+    await invoke('open_config_in_editor');
+    state.configEditing = true;
+  } catch (e) {
+    showToast('打开配置文件失败', 'error');
+  }
+}
+
+async function reloadConfig() {
+  try {
+    state.config = normalizeConfig(await invoke('reload_config'));
+    state.configEditing = false;
+    renderConfig();
+    showToast('配置已重新加载', 'success');
+  } catch (e) {
+    showToast(typeof e === 'string' && e ? `配置有误：${e}` : '重新加载配置失败', 'error');
+  }
+}
+
+async function captureDiagnostics() {
+  try {
+    const path = await invoke('capture_diagnostics');
+    showToast(`诊断信息已保存：${path}`, 'success');
+  } catch (e) {
+    showToast('导出诊断失败', 'error');
+  }
+}
+
+async function clearWatchLogs() {
+  try {
+    const { removed, skipped } = await invoke('clear_watch_logs');
+    if (skipped > 0) {
+      showToast(`已清空 ${removed} 个日志文件，${skipped} 个正在使用的文件未删除`, 'info');
+    } else {
+      showToast(`已清空 ${removed} 个日志文件`, 'success');
+    }
+  } catch (e) {
+    showToast('清空日志失败', 'error');
+  }
+}
+
 function showToast(message, type = 'info') {
   const toastContainer = document.getElementById('toast-container');
   const toast = document.createElement('div');